@@ -0,0 +1,206 @@
+struct type_4 {
+    member: f32;
+    member_1: f32;
+    member_2: f32;
+    member_3: f32;
+    member_4: vec4<f32>;
+    member_5: f32;
+    member_6: f32;
+    member_7: f32;
+    member_8: f32;
+    member_9: vec4<f32>;
+    member_10: vec4<f32>;
+};
+
+struct type_6 {
+    member: [[stride(80)]] array<type_4,3>;
+};
+
+struct type_8 {
+    member: vec2<f32>;
+    member_1: f32;
+    member_2: f32;
+};
+
+struct type_10 {
+    member: f32;
+    member_1: f32;
+    member_2: vec2<f32>;
+    member_3: f32;
+    member_4: f32;
+    member_5: f32;
+    member_6: f32;
+    member_7: [[stride(16)]] array<type_8,8>;
+};
+
+struct type_12 {
+    member: type_6;
+};
+
+struct type_14 {
+    member: type_10;
+};
+
+[[group(1), binding(0)]]
+var<uniform> global: type_12;
+[[group(1), binding(1)]]
+var<uniform> global_1: type_14;
+var<private> global_2: vec2<f32>;
+var<private> global_3: vec4<f32>;
+var<private> global_4: vec4<f32>;
+
+fn function_(param_2: type_4, param_3: f32, param_4: f32, param_5: f32, param_6: f32, param_7: f32) -> vec2<f32> {
+    let _e52 = ((((param_5 * param_3) + param_2.member_1) + param_7) + (((param_2.member_6 * param_2.member_2) * param_4) * sqrt(param_5)));
+    return vec2<f32>(((param_2.member * param_6) * sin(_e52)), ((param_2.member_5 / (param_5 * 3.0)) * cos(_e52)));
+}
+
+fn function_1(param_8: type_4, param_9: f32, param_10: f32) -> vec2<f32> {
+    let _e39 = function_(param_8, param_9, param_10, 1.0, 1.0, 0.0);
+    let _e40 = function_(param_8, param_9, param_10, 3.0, 0.30000001192092896, 1.7000000476837158);
+    let _e42 = function_(param_8, param_9, param_10, 7.0, 0.10000000149011612, 4.099999904632568);
+    return ((_e39 + _e40) + _e42);
+}
+
+fn function_2(param_11: type_4, param_12: f32, param_13: f32) -> f32 {
+    var local: f32;
+    var local_1: i32 = 0;
+
+    local = param_12;
+    loop {
+        let _e41 = local_1;
+        if ((_e41 >= 4)) {
+            break;
+        }
+        let _e43 = local;
+        let _e44 = function_1(param_11, _e43, param_13);
+        local = (param_12 + _e44.y);
+        let _e47 = local_1;
+        local_1 = (_e47 + 1);
+        continue;
+    }
+    let _e49 = local;
+    let _e50 = function_1(param_11, _e49, param_13);
+    return _e50.x;
+}
+
+fn function_3(param_14: type_8, param_15: f32) -> f32 {
+    let _e39 = (param_15 - param_14.member_1);
+    if (((param_14.member_2 <= 0.0) || (_e39 < 0.0))) {
+        return 0.0;
+    }
+    return max((1.0 - (_e39 / 3.0)), 0.0);
+}
+
+fn function_4(param_16: f32, param_17: f32) -> f32 {
+    var local_2: f32 = 0.0;
+    var local_3: u32 = 0u;
+
+    loop {
+        let _e41 = local_3;
+        if ((_e41 >= 8u)) {
+            break;
+        }
+        let _e43 = local_3;
+        let _e46 = global_1.member.member_7[_e43];
+        let _e47 = local_3;
+        local_3 = (_e47 + 1u);
+        let _e50 = global_1.member.member;
+        let _e51 = function_3(_e46, _e50);
+        if ((_e51 == 0.0)) {
+            continue;
+        }
+        let _e54 = global_1.member.member;
+        let _e60 = global_1.member.member_1;
+        let _e65 = (abs((param_16 - (_e46.member.x * _e60))) - (0.30000001192092896 * (_e54 - _e46.member_1)));
+        let _e79 = local_2;
+        local_2 = (_e79 + (((_e46.member_2 * exp((-((_e65 * _e65)) / (0.07999999821186066 * 0.07999999821186066)))) * (_e51 * exp((-(abs((_e46.member.y - param_17))) * 4.0)))) * cos((_e65 * 60.0))));
+        continue;
+    }
+    let _e87 = local_2;
+    return _e87;
+}
+
+fn function_5(param_18: f32, param_19: f32, param_20: f32) -> f32 {
+    let _e42 = clamp(((param_20 - param_18) / (param_19 - param_18)), 0.0, 1.0);
+    return ((_e42 * _e42) * (3.0 - (2.0 * _e42)));
+}
+
+fn function_6() {
+    var local_4: vec4<f32>;
+    var local_5: u32 = 0u;
+    var local_6: vec3<f32>;
+    var local_7: vec4<f32>;
+    var local_8: f32;
+
+    let _e41 = global_2;
+    let _e42 = global_3;
+    local_7 = vec4<f32>(0.0);
+    loop {
+        let _e46 = local_5;
+        if ((_e46 >= 3u)) {
+            break;
+        }
+        let _e48 = local_5;
+        let _e51 = global.member.member[_e48];
+        let _e52 = local_5;
+        local_5 = (_e52 + 1u);
+        if ((_e51.member_4.w == 0.0)) {
+            continue;
+        }
+        let _e59 = global_1.member.member_1;
+        let _e60 = (_e41.x * _e59);
+        let _e62 = global_1.member.member;
+        let _e63 = function_2(_e51, _e60, _e62);
+        let _e65 = function_4(_e60, _e51.member_3);
+        let _e66 = (_e63 + _e65);
+        let _e70 = (_e41.y - (_e66 + _e51.member_3));
+        local_8 = 0.0;
+        if ((_e51.member_8 > 0.0)) {
+            let _e76 = function_5(-(_e51.member_8), _e51.member_8, _e70);
+            local_8 = _e76;
+        } else {
+            if ((_e70 >= 0.0)) {
+                local_8 = 1.0;
+            }
+        }
+        let _e78 = local_8;
+        if ((_e78 == 0.0)) {
+            continue;
+        }
+        local_6 = mix(_e51.member_4.xyz, _e51.member_10.xyz, vec3<f32>(clamp((max(_e70, 0.0) / max((1.0 - (_e66 + _e51.member_3)), 0.0010000000474974513)), 0.0, 1.0)));
+        if ((_e51.member_7 > 0.0)) {
+            let _e102 = clamp((((-(_e66) / max((_e51.member * 1.399999976158142), 0.0010000000474974513)) * 0.5) + 0.5), 0.0, 1.0);
+            let _e105 = function_5(0.0, _e51.member_7, max(_e70, 0.0));
+            let _e107 = local_6;
+            local_6 = mix(_e107, _e51.member_9.xyz, vec3<f32>(((((1.0 - _e105) * _e102) * _e102) * _e51.member_9.w)));
+        }
+        let _e120 = global_1.member.member_2[1u];
+        let _e123 = local_6;
+        let _e130 = local_8;
+        let _e131 = (_e51.member_4.w * _e130);
+        let _e133 = local_7;
+        let _e139 = local_7[3u];
+        local_7 = vec4<f32>(((mix(_e123, normalize(vec3<f32>((_e42.xy / vec2<f32>(_e120)), 0.0)), vec3<f32>(0.20000000298023224)) * _e131) + (_e133.xyz * (1.0 - _e131))), (_e131 + (_e139 * (1.0 - _e131))));
+        continue;
+    }
+    local_4 = vec4<f32>(0.0);
+    let _e146 = local_7[3u];
+    if ((_e146 > 0.0)) {
+        let _e148 = local_7;
+        let _e151 = local_7[3u];
+        let _e155 = local_7[3u];
+        local_4 = vec4<f32>((_e148.xyz / vec3<f32>(_e151)), _e155);
+    }
+    let _e157 = local_4;
+    global_4 = _e157;
+    return;
+}
+
+[[stage(fragment)]]
+fn fragment([[location(2)]] param: vec2<f32>, [[builtin(position)]] param_1: vec4<f32>) -> [[location(0)]] vec4<f32> {
+    global_2 = param;
+    global_3 = param_1;
+    function_6();
+    let _e5 = global_4;
+    return _e5;
+}
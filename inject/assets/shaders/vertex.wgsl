@@ -0,0 +1,240 @@
+struct type_5 {
+    member: mat4x4<f32>;
+};
+
+struct type_6 {
+    member: mat4x4<f32>;
+    member_1: mat4x4<f32>;
+};
+
+struct type_7 {
+    member: f32;
+    member_1: f32;
+    member_2: f32;
+    member_3: f32;
+    member_4: vec4<f32>;
+    member_5: f32;
+    member_6: f32;
+    member_7: f32;
+    member_8: f32;
+    member_9: vec4<f32>;
+    member_10: vec4<f32>;
+};
+
+struct type_9 {
+    member: [[stride(80)]] array<type_7,3>;
+};
+
+struct type_11 {
+    member: vec2<f32>;
+    member_1: f32;
+    member_2: f32;
+};
+
+struct type_13 {
+    member: f32;
+    member_1: f32;
+    member_2: vec2<f32>;
+    member_3: f32;
+    member_4: f32;
+    member_5: f32;
+    member_6: f32;
+    member_7: [[stride(16)]] array<type_11,8>;
+};
+
+struct type_15 {
+    member: vec4<f32>;
+    member_1: vec4<f32>;
+    member_2: vec3<f32>;
+    member_3: vec2<f32>;
+};
+
+struct type_16 {
+    member: type_9;
+};
+
+struct type_18 {
+    member: type_13;
+};
+
+struct type_20 {
+    member: type_5;
+};
+
+struct type_22 {
+    member: type_6;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] member: vec4<f32>;
+    [[location(0)]] member_1: vec4<f32>;
+    [[location(1)]] member_2: vec3<f32>;
+    [[location(2)]] member_3: vec2<f32>;
+};
+
+[[group(1), binding(0)]]
+var<uniform> global: type_16;
+[[group(1), binding(1)]]
+var<uniform> global_1: type_18;
+[[group(0), binding(0)]]
+var<uniform> global_2: type_20;
+[[group(2), binding(0)]]
+var<uniform> global_3: type_22;
+var<private> global_4: vec3<f32>;
+var<private> global_5: vec3<f32>;
+var<private> global_6: vec2<f32>;
+var<private> global_7: vec4<f32> = vec4<f32>(0.0, 0.0, 0.0, 1.0);
+var<private> global_8: vec4<f32>;
+var<private> global_9: vec3<f32>;
+var<private> global_10: vec2<f32>;
+
+fn function_(param_3: type_7, param_4: f32, param_5: f32, param_6: f32, param_7: f32, param_8: f32) -> vec2<f32> {
+    let _e53 = ((((param_6 * param_4) + param_3.member_1) + param_8) + (((param_3.member_6 * param_3.member_2) * param_5) * sqrt(param_6)));
+    return vec2<f32>(((param_3.member * param_7) * sin(_e53)), ((param_3.member_5 / (param_6 * 3.0)) * cos(_e53)));
+}
+
+fn function_1(param_9: type_7, param_10: f32, param_11: f32) -> vec2<f32> {
+    let _e40 = function_(param_9, param_10, param_11, 1.0, 1.0, 0.0);
+    let _e41 = function_(param_9, param_10, param_11, 3.0, 0.30000001192092896, 1.7000000476837158);
+    let _e43 = function_(param_9, param_10, param_11, 7.0, 0.10000000149011612, 4.099999904632568);
+    return ((_e40 + _e41) + _e43);
+}
+
+fn function_2(param_12: type_7, param_13: f32, param_14: f32) -> f32 {
+    var local: f32;
+    var local_1: i32 = 0;
+
+    local = param_13;
+    loop {
+        let _e42 = local_1;
+        if ((_e42 >= 4)) {
+            break;
+        }
+        let _e44 = local;
+        let _e45 = function_1(param_12, _e44, param_14);
+        local = (param_13 + _e45.y);
+        let _e48 = local_1;
+        local_1 = (_e48 + 1);
+        continue;
+    }
+    let _e50 = local;
+    let _e51 = function_1(param_12, _e50, param_14);
+    return _e51.x;
+}
+
+fn function_3(param_15: type_11, param_16: f32) -> f32 {
+    let _e40 = (param_16 - param_15.member_1);
+    if (((param_15.member_2 <= 0.0) || (_e40 < 0.0))) {
+        return 0.0;
+    }
+    return max((1.0 - (_e40 / 3.0)), 0.0);
+}
+
+fn function_4() -> f32 {
+    var local_2: f32 = 0.0;
+    var local_3: u32 = 0u;
+
+    loop {
+        let _e40 = local_3;
+        if ((_e40 >= 8u)) {
+            break;
+        }
+        let _e42 = local_3;
+        let _e45 = global_1.member.member_7[_e42];
+        let _e46 = local_3;
+        local_3 = (_e46 + 1u);
+        let _e49 = global_1.member.member;
+        let _e50 = function_3(_e45, _e49);
+        if ((_e50 > 0.0)) {
+            let _e52 = local_2;
+            local_2 = (_e52 + _e45.member_2);
+        }
+        continue;
+    }
+    let _e55 = local_2;
+    return _e55;
+}
+
+fn function_5(param_17: f32) -> f32 {
+    var local_4: f32 = 1.0;
+    var local_5: u32 = 0u;
+
+    let _e43 = global_1.member.member_1;
+    let _e44 = (param_17 * _e43);
+    let _e46 = global_1.member.member_1;
+    let _e49 = global_1.member.member_3;
+    let _e50 = ((0.5 * _e46) / _e49);
+    let _e51 = function_4();
+    loop {
+        let _e52 = local_5;
+        if ((_e52 >= 3u)) {
+            break;
+        }
+        let _e54 = local_5;
+        let _e57 = global.member.member[_e54];
+        let _e58 = local_5;
+        local_5 = (_e58 + 1u);
+        if ((_e57.member_4.w == 0.0)) {
+            continue;
+        }
+        let _e64 = global_1.member.member;
+        let _e66 = function_2(_e57, (_e44 - _e50), _e64);
+        let _e67 = function_2(_e57, _e44, _e64);
+        let _e70 = function_2(_e57, (_e44 + _e50), _e64);
+        let _e87 = local_4;
+        local_4 = min(_e87, ((((_e57.member_3 + min(min(_e66, _e67), _e70)) - (((abs(_e57.member) * 2.5999999046325684) / max((1.0 - _e57.member_5), 0.10000000149011612)) * _e50)) - max(_e57.member_8, 0.0)) - _e51));
+        continue;
+    }
+    let _e89 = local_4;
+    return max(_e89, 0.0);
+}
+
+fn function_6() {
+    var local_6: vec3<f32>;
+    var local_7: vec2<f32>;
+    var local_8: type_15;
+
+    let _e40 = global_4;
+    let _e41 = global_5;
+    let _e42 = global_6;
+    local_6 = _e40;
+    local_7 = _e42;
+    let _e48 = global_1.member.member_3;
+    let _e51 = local_7[1u];
+    if (((_e48 > 1.0) && (_e51 <= 0.0))) {
+        let _e55 = local_7[0u];
+        let _e56 = function_5(_e55);
+        let _e58 = local_6[1u];
+        local_6[1u] = (_e58 * (1.0 - (2.0 * _e56)));
+        local_7[1u] = _e56;
+    }
+    let _e65 = global_3.member.member;
+    let _e66 = local_6;
+    let _e68 = (_e65 * vec4<f32>(_e66, 1.0));
+    local_8.member_1 = _e68;
+    let _e71 = global_3.member.member_1;
+    local_8.member_2 = (_e71 * vec4<f32>(_e41, 0.0)).xyz;
+    let _e76 = local_7;
+    local_8.member_3 = _e76;
+    let _e79 = global_2.member.member;
+    local_8.member = (_e79 * _e68);
+    let _e82 = local_8;
+    global_7 = _e82.member;
+    global_8 = _e82.member_1;
+    global_9 = _e82.member_2;
+    global_10 = _e82.member_3;
+    return;
+}
+
+[[stage(vertex)]]
+fn vertex([[location(0)]] param: vec3<f32>, [[location(1)]] param_1: vec3<f32>, [[location(2)]] param_2: vec2<f32>) -> VertexOutput {
+    global_4 = param;
+    global_5 = param_1;
+    global_6 = param_2;
+    function_6();
+    let _e10 = global_7;
+    let _e11 = global_8;
+    let _e12 = global_9;
+    let _e13 = global_10;
+    return VertexOutput(_e10, _e11, _e12, _e13);
+}
@@ -0,0 +1,353 @@
+//! Headless balance simulator.
+//!
+//! Plays whole runs without a window or renderer, with a scripted cursor, as fast as the
+//! machine allows and prints how long they lasted and how every wave went:
+//!
+//! ```sh
+//! cargo run --release --bin balance -- --games 20 --difficulty hard --policy sweep
+//! ```
+//!
+//! Options: `--games <n>`, `--seed <u64>` (first seed, the others count up from it),
+//! `--difficulty <easy|normal|hard|file>`, `--mode <classic|time-attack|endless|zen>`,
+//! `--policy <chase|sweep>` and `--max-seconds <f64>`.
+//! Build it without the `dev` feature, heron's debug renderer needs a window.
+
+use std::{fs, path::PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use bevy::{prelude::*, transform::TransformPlugin};
+use bevy_tweening::TweenCompleted;
+use heron::prelude::*;
+use inject::{
+    difficulty::{ActiveDifficulty, Difficulty, DifficultyPreset},
+    game_state::FishWarState,
+    inject::{Arena, Inject, InjectPluge, InjectSystem, Source, WaterLevel, WaveNumber},
+    mode::GameMode,
+    replay::FrameInput,
+    rng::{RngPlugin, SeedArg},
+    sim::{add_sim_system_set, SimPlugin, SimTime, SIM_STEP},
+};
+
+/// Cursor speed of the scripted player, in pixels per second.
+const CURSOR_SPEED: f32 = 1500.0;
+
+fn main() -> Result<()> {
+    let options = Options::from_args()?;
+    let max_frames = (options.max_seconds / SIM_STEP.as_secs_f64()).ceil() as u64;
+
+    let mut runs = Vec::new();
+    for game in 0..options.games {
+        let seed = options.seed.wrapping_add(game);
+        let run = play(&options, seed, max_frames);
+        println!("{}", run);
+        runs.push(run);
+    }
+
+    print_summary(&runs);
+    Ok(())
+}
+
+struct Options {
+    games: u64,
+    seed: u64,
+    difficulty: Difficulty,
+    mode: GameMode,
+    policy: Policy,
+    max_seconds: f64,
+}
+
+impl Options {
+    fn from_args() -> Result<Self> {
+        let mut options = Self {
+            games: 10,
+            seed: 0,
+            difficulty: load_difficulty("normal")?,
+            mode: GameMode::Classic,
+            policy: Policy::Chase,
+            max_seconds: 600.0,
+        };
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+            match arg.as_str() {
+                "--games" => options.games = value()?.parse()?,
+                "--seed" => options.seed = value()?.parse()?,
+                "--difficulty" => options.difficulty = load_difficulty(&value()?)?,
+                "--mode" => {
+                    let mode = value()?;
+                    options.mode = GameMode::ALL
+                        .into_iter()
+                        .find(|m| m.name().replace(' ', "-").eq_ignore_ascii_case(&mode))
+                        .ok_or_else(|| anyhow!("unknown mode {:?}", mode))?;
+                }
+                "--policy" => {
+                    options.policy = match value()?.as_str() {
+                        "chase" => Policy::Chase,
+                        "sweep" => Policy::Sweep,
+                        policy => bail!("unknown policy {:?}, expected chase or sweep", policy),
+                    }
+                }
+                "--max-seconds" => options.max_seconds = value()?.parse()?,
+                _ => bail!("unknown argument {:?}", arg),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Loads a preset by name, or any `*.difficulty.ron` file.
+fn load_difficulty(name: &str) -> Result<Difficulty> {
+    let path = DifficultyPreset::ALL
+        .iter()
+        .find(|preset| preset.name().eq_ignore_ascii_case(name))
+        .map(|preset| {
+            PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("assets")
+                .join(preset.path())
+        })
+        .unwrap_or_else(|| PathBuf::from(name));
+    Difficulty::from_ron(&fs::read(&path)?).map_err(|e| anyhow!("{:?}: {:#}", path, e))
+}
+
+/// How the scripted player moves the cursor.
+#[derive(Debug, Clone, Copy)]
+enum Policy {
+    /// Hops from the closest inject to the next one.
+    Chase,
+    /// Sweeps the whole arena along a fixed figure, ignoring the injects.
+    Sweep,
+}
+
+/// State of the scripted player.
+struct Player {
+    policy: Policy,
+    cursor: Vec2,
+    /// Inject touched last, left alone until the cursor moved on.
+    touched: Option<Entity>,
+}
+
+#[derive(Default)]
+struct WaveStats {
+    pops: u32,
+    water_start: f32,
+    water_end: f32,
+}
+
+#[derive(Default)]
+struct RunStats {
+    seed: u64,
+    seconds: f64,
+    timed_out: bool,
+    waves: Vec<WaveStats>,
+}
+
+fn play(options: &Options, seed: u64, max_frames: u64) -> RunStats {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(TransformPlugin)
+        .add_event::<TweenCompleted>()
+        .add_plugin(RngPlugin)
+        .add_plugin(SimPlugin)
+        .add_plugin(InjectPluge)
+        .insert_resource(SeedArg(Some(seed)))
+        .insert_resource(ActiveDifficulty(options.difficulty.clone()))
+        .insert_resource(options.mode)
+        .init_resource::<FrameInput>()
+        .insert_resource(Player {
+            policy: options.policy,
+            cursor: Vec2::ZERO,
+            touched: None,
+        })
+        .insert_resource(RunStats {
+            seed,
+            ..Default::default()
+        })
+        .add_state(FishWarState::Game)
+        .add_system_to_stage(CoreStage::PreUpdate, move_cursor);
+    add_sim_system_set(
+        &mut app,
+        SystemSet::on_update(FishWarState::Game).with_system(
+            track_waves
+                .after(InjectSystem::Drain)
+                .before(InjectSystem::NextWave),
+        ),
+    );
+
+    for _ in 0..max_frames {
+        app.update();
+        if state(&app) != FishWarState::Game {
+            break;
+        }
+    }
+
+    let seconds = app.world.get_resource::<SimTime>().unwrap().seconds();
+    let timed_out = state(&app) != FishWarState::GameOver;
+    let mut stats = app.world.remove_resource::<RunStats>().unwrap();
+    stats.seconds = seconds;
+    stats.timed_out = timed_out;
+    stats
+}
+
+fn state(app: &App) -> FishWarState {
+    app.world
+        .get_resource::<State<FishWarState>>()
+        .unwrap()
+        .current()
+        .clone()
+}
+
+fn move_cursor(
+    mut player: ResMut<Player>,
+    mut input: ResMut<FrameInput>,
+    arena: Res<Arena>,
+    sim_time: Res<SimTime>,
+    injects: Query<(Entity, &Transform, &CollisionShape), With<Inject>>,
+) {
+    let player = &mut *player;
+    let target = match player.policy {
+        Policy::Chase => {
+            let radius = |shape: &CollisionShape| match shape {
+                CollisionShape::Sphere { radius } => *radius,
+                _ => 0.0,
+            };
+            let cursor = player.cursor;
+
+            // Forget the touched inject once the cursor left it, or it was popped.
+            let left = match player.touched.and_then(|entity| injects.get(entity).ok()) {
+                Some((_, transform, shape)) => {
+                    transform.translation.truncate().distance(cursor) > radius(shape) + 1.0
+                }
+                None => true,
+            };
+            if left {
+                player.touched = None;
+            }
+
+            let closest = injects
+                .iter()
+                .filter(|(entity, ..)| Some(*entity) != player.touched)
+                .min_by(|(_, a, _), (_, b, _)| {
+                    let a = a.translation.truncate().distance_squared(cursor);
+                    let b = b.translation.truncate().distance_squared(cursor);
+                    a.total_cmp(&b)
+                });
+
+            match closest {
+                Some((entity, transform, shape)) => {
+                    let center = transform.translation.truncate();
+                    if center.distance(cursor) < radius(shape) {
+                        player.touched = Some(entity);
+                    }
+                    center
+                }
+                // Only the touched inject is left, step out of it to touch it again.
+                None => match player.touched.and_then(|e| injects.get(e).ok()) {
+                    Some((_, transform, shape)) => {
+                        let center = transform.translation.truncate();
+                        let away = (cursor - center).try_normalize().unwrap_or(Vec2::X);
+                        center + away * (radius(shape) + 10.0)
+                    }
+                    None => cursor,
+                },
+            }
+        }
+        Policy::Sweep => {
+            let t = sim_time.seconds() as f32;
+            arena.0 * 0.45 * Vec2::new((t * 0.7).sin(), (t * 1.1).sin())
+        }
+    };
+
+    let step = CURSOR_SPEED * SIM_STEP.as_secs_f32();
+    let to_target = target - player.cursor;
+    player.cursor += to_target.clamp_length_max(step);
+    input.cursor = Some(player.cursor);
+    // One tick per update, the runs are not bound to the wall clock.
+    input.ticks = 1;
+}
+
+fn track_waves(
+    mut stats: ResMut<RunStats>,
+    wave: Res<WaveNumber>,
+    water: Res<WaterLevel>,
+    mut source: EventReader<Source>,
+) {
+    let index = usize::from(wave.0.max(1)) - 1;
+    while stats.waves.len() <= index {
+        let water_start = stats.waves.last().map_or(water.0, |last| last.water_end);
+        stats.waves.push(WaveStats {
+            water_start,
+            water_end: water_start,
+            ..Default::default()
+        });
+    }
+
+    let current = &mut stats.waves[index];
+    current.pops += source.iter().count() as u32;
+    current.water_end = water.0;
+}
+
+impl std::fmt::Display for RunStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "seed {}: {} after {:.1}s, {} waves",
+            self.seed,
+            if self.timed_out {
+                "timed out"
+            } else {
+                "game over"
+            },
+            self.seconds,
+            self.waves.len()
+        )?;
+        for (i, wave) in self.waves.iter().enumerate() {
+            writeln!(
+                f,
+                "  wave {:>3}: {:>3} pops, water {:.3} -> {:.3}",
+                i + 1,
+                wave.pops,
+                wave.water_start,
+                wave.water_end
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn print_summary(runs: &[RunStats]) {
+    if runs.is_empty() {
+        return;
+    }
+
+    let seconds: Vec<f64> = runs.iter().map(|run| run.seconds).collect();
+    let mean = seconds.iter().sum::<f64>() / seconds.len() as f64;
+    let min = seconds.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = seconds.iter().copied().fold(0.0, f64::max);
+    let timed_out = runs.iter().filter(|run| run.timed_out).count();
+    println!(
+        "{} runs, {} timed out, time to game over: mean {:.1}s, min {:.1}s, max {:.1}s",
+        runs.len(),
+        timed_out,
+        mean,
+        min,
+        max
+    );
+
+    let waves = runs.iter().map(|run| run.waves.len()).max().unwrap_or(0);
+    for i in 0..waves {
+        let reached: Vec<&WaveStats> = runs.iter().filter_map(|run| run.waves.get(i)).collect();
+        let n = reached.len() as f32;
+        println!(
+            "  wave {:>3}: reached by {:>3}, {:>5.1} pops, water drop {:.3}",
+            i + 1,
+            reached.len(),
+            reached.iter().map(|wave| wave.pops as f32).sum::<f32>() / n,
+            reached
+                .iter()
+                .map(|wave| wave.water_start - wave.water_end)
+                .sum::<f32>()
+                / n
+        );
+    }
+}
@@ -0,0 +1,27 @@
+//! Reads a `*.difficulty.ron` file from stdin and checks it the way the game loads it: parsed
+//! into [`Difficulty`] and validated. Prints the error and exits with 1 when it is rejected.
+//!
+//! `cargo xtask check-assets` runs it on every preset the game references:
+//!
+//! ```sh
+//! cargo run --bin check_difficulty < inject/assets/difficulty/normal.difficulty.ron
+//! ```
+
+use std::{
+    io::{self, Read},
+    process,
+};
+
+use inject::difficulty::Difficulty;
+
+fn main() {
+    let mut bytes = Vec::new();
+    let result = io::stdin()
+        .read_to_end(&mut bytes)
+        .map_err(anyhow::Error::from)
+        .and_then(|_| Difficulty::from_ron(&bytes));
+    if let Err(e) = result {
+        eprintln!("{:#}", e);
+        process::exit(1);
+    }
+}
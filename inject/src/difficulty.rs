@@ -0,0 +1,309 @@
+use std::ops::Range;
+
+use anyhow::ensure;
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use codec::{Decode, Encode, MaxEncodedLen};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{inject::InjectKind, replay::InputSource};
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Difficulty>()
+            .init_asset_loader::<DifficultyLoader>()
+            .init_resource::<DifficultyPreset>()
+            .init_resource::<ActiveDifficulty>()
+            .add_startup_system(load_presets)
+            .add_system(apply_difficulty);
+    }
+}
+
+/// Balancing of a whole run, loaded from `assets/difficulty/*.difficulty.ron`.
+#[derive(Debug, Clone, Deserialize, Serialize, TypeUuid)]
+#[uuid = "5d0f6e2b-7f3c-4b8e-9a51-2c6f0e1d8b34"]
+#[serde(deny_unknown_fields)]
+pub struct Difficulty {
+    /// Waves in play order, the last one repeats once the list runs out.
+    pub waves: Vec<WaveSettings>,
+    /// Injects added to every wave played past the end of `waves`.
+    #[serde(default)]
+    pub extra_injects: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct WaveSettings {
+    /// Injects spawned at the start of the wave.
+    pub injects: u8,
+    /// Radius of a freshly spawned inject.
+    pub init_radius: f32,
+    /// Radius an inject has to reach before the mouse can pop it.
+    pub enough_radius: f32,
+    /// Speed of a freshly spawned inject, the direction is random.
+    pub velocity: Range<f32>,
+    /// Scale factor applied every time the mouse touches an inject.
+    pub growth: Range<f32>,
+    /// Water lowered by every pop.
+    pub water_drop: Range<f32>,
+    /// Which kinds of injects the wave spawns, only normal ones if left out.
+    #[serde(default)]
+    pub kinds: KindWeights,
+}
+
+/// Relative spawn chance of every [`InjectKind`], e.g. `(normal: 8, bomb: 1)`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KindWeights {
+    pub normal: u32,
+    pub bomb: u32,
+    pub golden: u32,
+    pub splitter: u32,
+    pub heavy: u32,
+}
+
+impl Default for KindWeights {
+    fn default() -> Self {
+        Self {
+            normal: 1,
+            bomb: 0,
+            golden: 0,
+            splitter: 0,
+            heavy: 0,
+        }
+    }
+}
+
+impl KindWeights {
+    fn weights(&self) -> [(InjectKind, u32); 5] {
+        [
+            (InjectKind::Normal, self.normal),
+            (InjectKind::Bomb, self.bomb),
+            (InjectKind::Golden, self.golden),
+            (InjectKind::Splitter, self.splitter),
+            (InjectKind::Heavy, self.heavy),
+        ]
+    }
+
+    fn total(&self) -> u32 {
+        self.weights().iter().map(|(_, weight)| weight).sum()
+    }
+
+    pub fn pick(&self, rng: &mut impl Rng) -> InjectKind {
+        let total = self.total();
+        if total == 0 {
+            return InjectKind::Normal;
+        }
+
+        let mut roll = rng.gen_range(0..total);
+        for (kind, weight) in self.weights() {
+            if roll < weight {
+                return kind;
+            }
+            roll -= weight;
+        }
+        InjectKind::Normal
+    }
+}
+
+impl Difficulty {
+    /// Settings of the given wave, starting from 1.
+    pub fn wave(&self, wave: u8) -> WaveSettings {
+        let index = usize::from(wave.max(1)) - 1;
+        match self.waves.get(index) {
+            Some(settings) => settings.clone(),
+            None => {
+                let last = self.waves.last().cloned().unwrap_or_default();
+                let extra = (index + 1 - self.waves.len()) * usize::from(self.extra_injects);
+                WaveSettings {
+                    injects: (usize::from(last.injects) + extra).min(u8::MAX.into()) as u8,
+                    ..last
+                }
+            }
+        }
+    }
+
+    /// Parses and validates a `*.difficulty.ron` file, a misspelled field is an error rather
+    /// than left at its default.
+    pub fn from_ron(bytes: &[u8]) -> anyhow::Result<Self> {
+        let difficulty: Self = ron::de::from_bytes(bytes)?;
+        difficulty.validate()?;
+        Ok(difficulty)
+    }
+
+    /// Rejects settings the game can not play, e.g. a wave without injects.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        ensure!(!self.waves.is_empty(), "at least one wave is required");
+        for (i, wave) in self.waves.iter().enumerate() {
+            ensure!(wave.injects > 0, "wave {} has no injects", i + 1);
+            ensure!(
+                wave.init_radius > 0.0 && wave.init_radius < wave.enough_radius,
+                "wave {}: init_radius must be positive and below enough_radius",
+                i + 1
+            );
+            ensure!(
+                wave.kinds.total() > 0,
+                "wave {}: all kind weights are 0",
+                i + 1
+            );
+            for (name, range) in [
+                ("velocity", &wave.velocity),
+                ("growth", &wave.growth),
+                ("water_drop", &wave.water_drop),
+            ] {
+                ensure!(
+                    range.start < range.end,
+                    "wave {}: empty {} range",
+                    i + 1,
+                    name
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Same as `assets/difficulty/normal.difficulty.ron`, used until that file is loaded.
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self {
+            waves: vec![
+                WaveSettings::default(),
+                WaveSettings {
+                    injects: 4,
+                    kinds: KindWeights {
+                        normal: 12,
+                        bomb: 1,
+                        golden: 1,
+                        splitter: 1,
+                        heavy: 1,
+                    },
+                    ..Default::default()
+                },
+            ],
+            extra_injects: 1,
+        }
+    }
+}
+
+impl Default for WaveSettings {
+    fn default() -> Self {
+        Self {
+            injects: 3,
+            init_radius: 20.0,
+            enough_radius: 50.0,
+            velocity: 50.0..80.0,
+            growth: 1.5..3.6,
+            water_drop: 0.01..0.02,
+            kinds: KindWeights::default(),
+        }
+    }
+}
+
+/// The difficulty the next run is played with.
+#[derive(Debug, Default)]
+pub struct ActiveDifficulty(pub Difficulty);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, MaxEncodedLen)]
+pub enum DifficultyPreset {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Default for DifficultyPreset {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+impl DifficultyPreset {
+    pub const ALL: [Self; 3] = [Self::Easy, Self::Normal, Self::Hard];
+
+    pub fn next(self) -> Self {
+        Self::ALL[(self as usize + 1) % Self::ALL.len()]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Easy => "Easy",
+            Self::Normal => "Normal",
+            Self::Hard => "Hard",
+        }
+    }
+
+    /// Asset path of the preset, relative to `assets`.
+    pub fn path(self) -> &'static str {
+        match self {
+            Self::Easy => "difficulty/easy.difficulty.ron",
+            Self::Normal => "difficulty/normal.difficulty.ron",
+            Self::Hard => "difficulty/hard.difficulty.ron",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct DifficultyLoader;
+
+impl AssetLoader for DifficultyLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let difficulty = Difficulty::from_ron(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(difficulty));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["difficulty.ron"]
+    }
+}
+
+/// Handles of every preset, indexed by `DifficultyPreset as usize`.
+struct DifficultyHandles([Handle<Difficulty>; 3]);
+
+fn load_presets(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(DifficultyHandles(
+        DifficultyPreset::ALL.map(|preset| asset_server.load(preset.path())),
+    ));
+}
+
+/// Copies the selected preset into [`ActiveDifficulty`] when it is picked, loaded or hot reloaded.
+///
+/// A replay brings its own difficulty, presets are left alone until it ends.
+fn apply_difficulty(
+    source: Res<InputSource>,
+    preset: Res<DifficultyPreset>,
+    handles: Res<DifficultyHandles>,
+    difficulties: Res<Assets<Difficulty>>,
+    mut events: EventReader<AssetEvent<Difficulty>>,
+    mut active: ResMut<ActiveDifficulty>,
+) {
+    if source.is_replay() {
+        return;
+    }
+
+    let handle = &handles.0[*preset as usize];
+    let reloaded = events.iter().any(|event| match event {
+        AssetEvent::Created { handle: h } | AssetEvent::Modified { handle: h } => h == handle,
+        AssetEvent::Removed { .. } => false,
+    });
+
+    if preset.is_changed() || reloaded {
+        if let Some(difficulty) = difficulties.get(handle) {
+            info!("difficulty set to {}", preset.name());
+            active.0 = difficulty.clone();
+        }
+    }
+}
@@ -1,31 +1,107 @@
 use bevy::{prelude::*, window::WindowCloseRequested};
 
-use crate::{game_state::FishWarState, utils::despawn_screen};
+use crate::{
+    game_state::FishWarState,
+    highscore::{HighScores, RecordRun},
+    mode::ModeRules,
+    replay::FrameInput,
+    rng::GameRng,
+    score::Score,
+    utils::despawn_screen,
+};
 
 pub struct GameOverPlugin;
 
 impl Plugin for GameOverPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(SystemSet::on_enter(FishWarState::GameOver).with_system(setup))
-            .add_system_set(
-                SystemSet::on_update(FishWarState::GameOver)
-                    .with_system(quit_game)
-                    .with_system(reopen_game),
-            )
-            .add_system_set(
-                SystemSet::on_exit(FishWarState::GameOver)
-                    .with_system(despawn_screen::<GameOverDespawn>),
-            );
+        app.add_system_set(
+            SystemSet::on_enter(FishWarState::GameOver).with_system(setup.after(RecordRun)),
+        )
+        .add_system_set(
+            SystemSet::on_update(FishWarState::GameOver)
+                .with_system(quit_game)
+                .with_system(reopen_game),
+        )
+        .add_system_set(
+            SystemSet::on_exit(FishWarState::GameOver)
+                .with_system(despawn_screen::<GameOverDespawn>),
+        );
     }
 }
 #[derive(Component)]
 struct GameOverDespawn;
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    score: Res<Score>,
+    high_scores: Res<HighScores>,
+    rng: Res<GameRng>,
+    rules: Res<ModeRules>,
+) {
     commands
         .spawn_bundle(UiCameraBundle::default())
         .insert(GameOverDespawn);
 
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(10.0),
+                    left: Val::Px(15.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                format!(
+                    "{}   Score: {}   Pops: {}   Best combo: {}   Seed: {}{}",
+                    rules.mode.name(),
+                    score.points,
+                    score.pops,
+                    score.best_combo,
+                    rng.seed(),
+                    high_scores
+                        .last_rank()
+                        .map(|rank| format!("   New high score #{}!", rank + 1))
+                        .unwrap_or_default()
+                ),
+                TextStyle {
+                    font: asset_server.load("fonts/finger-paint-regular.ttf"),
+                    font_size: 40.0,
+                    color: Color::GOLD,
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        })
+        .insert(GameOverDespawn);
+
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(70.0),
+                    right: Val::Px(15.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                high_scores.table(rules.mode),
+                TextStyle {
+                    font: asset_server.load("fonts/finger-paint-regular.ttf"),
+                    font_size: 28.0,
+                    color: Color::GOLD,
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        })
+        .insert(GameOverDespawn);
+
     commands
         .spawn_bundle(NodeBundle {
             style: Style {
@@ -47,7 +123,7 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
         .with_children(|parent| {
             parent.spawn_bundle(TextBundle {
                 text: Text::with_section(
-                    "Game Over! Have Fun? \n Please press 'ecs' button or 'Q'\n button to exit the game.\n Or 'M' button to go back\n to the menu and 'G' button to restart\n the game.\n Did you find the 'space' button\n in the game can trigger the Unfair\n Advantage?",
+                    "Game Over! Have Fun? \n Please press 'Esc' button or 'Q'\n button to exit the game.\n Or 'M' button to go back\n to the menu and 'G' button to restart\n the game.\n Did you find the 'space' button\n in the game can trigger the Unfair\n Advantage?",
                     TextStyle {
                         font: asset_server.load("fonts/finger-paint-regular.ttf"),
                         font_size: 80.0,
@@ -60,25 +136,25 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
         });
 }
 
-fn quit_game(
-    keyboard_input: Res<Input<KeyCode>>,
+pub fn quit_game(
+    input: Res<FrameInput>,
     mut quit: EventWriter<WindowCloseRequested>,
     windows: Res<Windows>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Q) || keyboard_input.just_pressed(KeyCode::Escape) {
+    if input.quit || input.back {
         quit.send(WindowCloseRequested {
             id: windows.get_primary().unwrap().id(),
         })
     }
 }
 
-fn reopen_game(keyboard_input: Res<Input<KeyCode>>, mut game_state: ResMut<State<FishWarState>>) {
-    if keyboard_input.just_pressed(KeyCode::M) {
+pub fn reopen_game(input: Res<FrameInput>, mut game_state: ResMut<State<FishWarState>>) {
+    if input.menu {
         if let Err(e) = game_state.set(FishWarState::Menu) {
             warn!("set state error: {:?}", e);
         };
     }
-    if keyboard_input.just_pressed(KeyCode::G) {
+    if input.restart {
         if let Err(e) = game_state.set(FishWarState::Game) {
             warn!("set state error: {:?}", e);
         };
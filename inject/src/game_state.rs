@@ -3,5 +3,9 @@
 pub enum FishWarState {
     Menu,
     Game,
+    /// Pushed on top of `Game`, so the run is kept while the overlay is shown.
+    Paused,
     GameOver,
+    /// The run met its `WinCondition`.
+    Victory,
 }
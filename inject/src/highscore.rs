@@ -0,0 +1,277 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{bail, ensure, Result};
+use bevy::prelude::*;
+use codec::{Compact, Decode, Encode, MaxEncodedLen};
+
+use crate::{
+    difficulty::DifficultyPreset,
+    game_state::FishWarState,
+    inject::WaveNumber,
+    mode::{GameMode, ModeRules},
+    replay::{FrameInput, InputSource},
+    score::Score,
+    sim::SimTime,
+};
+
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_high_scores)
+            .add_system_set(SystemSet::on_enter(FishWarState::Game).with_system(start_run))
+            .add_system_set(SystemSet::on_update(FishWarState::Game).with_system(track_run))
+            .add_system_set(
+                SystemSet::on_enter(FishWarState::GameOver)
+                    .with_system(record_run.label(RecordRun)),
+            )
+            .add_system_set(
+                SystemSet::on_enter(FishWarState::Victory).with_system(record_run.label(RecordRun)),
+            );
+    }
+}
+
+/// Runs on `on_enter` of `FishWarState::GameOver` and `FishWarState::Victory`, once the finished
+/// run is in [`HighScores`].
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RecordRun;
+
+/// Bump this whenever [`Record`] changes, older files are then backed up and replaced.
+const VERSION: u8 = 2;
+/// Records kept per [`GameMode`].
+const MAX_RECORDS: usize = 10;
+const FILE_NAME: &str = "highscores.bin";
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode, MaxEncodedLen)]
+pub struct Record {
+    pub score: u32,
+    /// Run length in milliseconds.
+    pub duration: u32,
+    pub waves: u8,
+    /// Whether the Space "Unfair Advantage" was used during the run.
+    pub unfair: bool,
+    pub mode: GameMode,
+    pub difficulty: DifficultyPreset,
+}
+
+/// [`Record`] as saved by version 1, before runs were told apart by mode and difficulty.
+#[derive(Decode)]
+struct RecordV1 {
+    score: u32,
+    duration: u32,
+    waves: u8,
+    unfair: bool,
+}
+
+impl From<RecordV1> for Record {
+    /// Modes came later than the table, the old runs were most likely Classic ones.
+    fn from(record: RecordV1) -> Self {
+        Self {
+            score: record.score,
+            duration: record.duration,
+            waves: record.waves,
+            unfair: record.unfair,
+            mode: GameMode::Classic,
+            difficulty: DifficultyPreset::Normal,
+        }
+    }
+}
+
+/// Top ten finished runs of every [`GameMode`], best first.
+#[derive(Debug, Default)]
+pub struct HighScores {
+    /// One table per mode, in the order of `GameMode::ALL`.
+    tables: [Vec<Record>; GameMode::ALL.len()],
+    path: Option<PathBuf>,
+    /// Rank of the run recorded last, if it made it into the table.
+    last_rank: Option<usize>,
+}
+
+impl HighScores {
+    pub fn last_rank(&self) -> Option<usize> {
+        self.last_rank
+    }
+
+    /// Renders the table of `mode` as plain text, one run per line.
+    pub fn table(&self, mode: GameMode) -> String {
+        let records = self.records(mode);
+        if records.is_empty() {
+            return format!("No {} high scores yet", mode.name());
+        }
+
+        let mut table = format!("{} High Scores\n", mode.name());
+        for (rank, record) in records.iter().enumerate() {
+            let seconds = record.duration / 1000;
+            table.push_str(&format!(
+                "{:>2}. {:>6}  {:02}:{:02}  wave {:<3} {}{}\n",
+                rank + 1,
+                record.score,
+                seconds / 60,
+                seconds % 60,
+                record.waves,
+                record.difficulty.name(),
+                if record.unfair { " *" } else { "" },
+            ));
+        }
+        table
+    }
+
+    fn load() -> Self {
+        match dirs::config_dir() {
+            Some(dir) => Self::load_from(dir.join("fishwar").join(FILE_NAME)),
+            None => Self::default(),
+        }
+    }
+
+    /// Reads the table saved at `path`. An unreadable file is backed up to `*.bin.bak` and
+    /// replaced by an empty table on the next save.
+    pub fn load_from(path: PathBuf) -> Self {
+        let mut high_scores = Self {
+            path: Some(path.clone()),
+            ..Default::default()
+        };
+        let records = if path.exists() {
+            match fs::read(&path) {
+                Ok(bytes) => decode(&bytes).unwrap_or_else(|e| {
+                    let backup = path.with_extension("bin.bak");
+                    error!(
+                        "high score file {:?} is unreadable ({:#}), backing it up to {:?}",
+                        path, e, backup
+                    );
+                    if let Err(e) = fs::rename(&path, &backup) {
+                        warn!("back up high scores error: {:?}", e);
+                    }
+                    Vec::new()
+                }),
+                Err(e) => {
+                    warn!("read high scores error: {:?}", e);
+                    Vec::new()
+                }
+            }
+        } else {
+            Vec::new()
+        };
+
+        // Saved best first, so every run is ranked below the ones before it.
+        for record in records {
+            high_scores.insert(record);
+        }
+        high_scores.last_rank = None;
+        high_scores
+    }
+
+    pub fn records(&self, mode: GameMode) -> &[Record] {
+        &self.tables[mode as usize]
+    }
+
+    /// Ranks the run among the records of its mode, ties go below the older runs.
+    pub fn insert(&mut self, record: Record) {
+        let records = &mut self.tables[record.mode as usize];
+        let rank = records
+            .iter()
+            .position(|r| r.score < record.score)
+            .unwrap_or(records.len());
+        if rank < MAX_RECORDS {
+            records.insert(rank, record);
+            records.truncate(MAX_RECORDS);
+            self.last_rank = Some(rank);
+        } else {
+            self.last_rank = None;
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(path) = &self.path {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(path, encode(&self.tables.concat()))?;
+        }
+        Ok(())
+    }
+}
+
+pub fn encode(records: &[Record]) -> Vec<u8> {
+    let mut bytes = VERSION.encode();
+    records.encode_to(&mut bytes);
+    bytes
+}
+
+/// Reads saved tables, rejecting unknown versions and anything longer than every table full.
+///
+/// Version 1 tables are taken over, see [`RecordV1`].
+pub fn decode(mut bytes: &[u8]) -> Result<Vec<Record>> {
+    let max_len = VERSION.encoded_size()
+        + Compact::<u32>::max_encoded_len()
+        + GameMode::ALL.len() * MAX_RECORDS * Record::max_encoded_len();
+    ensure!(
+        bytes.len() <= max_len,
+        "{} bytes, expected at most {}",
+        bytes.len(),
+        max_len
+    );
+
+    let input = &mut bytes;
+    let records = match u8::decode(input)? {
+        VERSION => Vec::<Record>::decode(input)?,
+        1 => Vec::<RecordV1>::decode(input)?
+            .into_iter()
+            .map(Record::from)
+            .collect(),
+        version => bail!("version {}, expected {}", version, VERSION),
+    };
+    ensure!(input.is_empty(), "{} trailing bytes", input.len());
+    Ok(records)
+}
+
+/// The run being played, turned into a [`Record`] at game over.
+#[derive(Debug, Default)]
+struct CurrentRun {
+    waves: u8,
+    unfair: bool,
+}
+
+fn load_high_scores(mut commands: Commands) {
+    commands.insert_resource(HighScores::load());
+}
+
+fn start_run(mut commands: Commands) {
+    commands.insert_resource(CurrentRun::default());
+}
+
+fn track_run(mut run: ResMut<CurrentRun>, wave: Res<WaveNumber>, input: Res<FrameInput>) {
+    run.waves = wave.0.saturating_sub(1);
+    if input.unfair {
+        run.unfair = true;
+    }
+}
+
+/// Replayed runs were recorded already when they were played, they only show their score.
+fn record_run(
+    run: Res<CurrentRun>,
+    score: Res<Score>,
+    rules: Res<ModeRules>,
+    preset: Res<DifficultyPreset>,
+    sim_time: Res<SimTime>,
+    source: Res<InputSource>,
+    mut high_scores: ResMut<HighScores>,
+) {
+    if source.is_replay() {
+        info!("replayed run scored {}", score.points);
+        high_scores.last_rank = None;
+        return;
+    }
+
+    high_scores.insert(Record {
+        score: score.points,
+        duration: (sim_time.seconds() * 1000.0) as u32,
+        waves: run.waves,
+        unfair: run.unfair,
+        mode: rules.mode,
+        difficulty: *preset,
+    });
+
+    if let Err(e) = high_scores.save() {
+        warn!("save high scores error: {:?}", e);
+    }
+}
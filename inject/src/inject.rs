@@ -1,22 +1,25 @@
-use std::time::Duration;
+use std::{ops::Range, time::Duration};
 
-use bevy::window::WindowResized;
-use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy::{ecs::system::SystemParam, prelude::*, sprite::MaterialMesh2dBundle};
 
 use bevy_tweening::AssetAnimator;
-use bevy_tweening::{
-    component_animator_system, lens::TransformScaleLens, Animator, EaseFunction, EaseMethod, Lens,
-    Lerp, Tween, TweeningType,
-};
+use bevy_tweening::{lens::TransformScaleLens, EaseFunction, Lens, Lerp, Tween, TweeningType};
 use heron::prelude::*;
 use rand::Rng;
 
 use crate::start_page::Wave;
 use crate::{
+    difficulty::{ActiveDifficulty, WaveSettings},
     game_state::FishWarState,
+    mode::{GameMode, ModeRules},
+    replay::FrameInput,
+    rng::{GameRng, Reseed},
+    sim::{add_sim_system_set, sim_animator_system, SimAnimator, SimTime, SIM_STEP},
     start_page::new_waves_mesh,
     utils::despawn_screen,
-    waves::{WavesMaterial, WavesPropertiesLens},
+    waves::{
+        waves_tween, world_to_uv, Ripples, WavesMaterial, WavesMesh, WavesTime, WavesViewport,
+    },
 };
 
 pub struct InjectPluge;
@@ -24,58 +27,248 @@ pub struct InjectPluge;
 impl Plugin for InjectPluge {
     fn build(&self, app: &mut App) {
         app.add_event::<Source>()
-            .add_system_set(SystemSet::on_enter(FishWarState::Game).with_system(setup))
+            .init_resource::<Arena>()
+            .init_resource::<GameMode>()
             .add_system_set(
-                SystemSet::on_update(FishWarState::Game)
-                    .with_system(space_to_unfair)
-                    .with_system(handle_inject)
-                    .with_system(sync_with_window_size)
-                    .with_system(sync_mouse_postion)
-                    .with_system(sync_with_time)
-                    .with_system(gen_new_inject)
-                    .with_system(component_animator_system::<CollisionShape>)
-                    .with_system(crate::start_page::sync_with_window_size),
+                SystemSet::on_enter(FishWarState::Game).with_system(setup.after(Reseed)),
             )
             .add_system_set(
                 SystemSet::on_exit(FishWarState::Game)
                     .with_system(despawn_screen::<InjecDespawn>)
                     .with_system(remove_resource),
             );
+        add_sim_system_set(
+            app,
+            SystemSet::on_update(FishWarState::Game)
+                .with_system(sync_with_arena_size.label(InjectSystem::Input))
+                .with_system(sync_mouse_postion.label(InjectSystem::Input))
+                .with_system(sync_wave_settings.label(InjectSystem::Input))
+                .with_system(
+                    sim_animator_system::<Transform>
+                        .label(InjectSystem::Animate)
+                        .after(InjectSystem::Input),
+                )
+                .with_system(
+                    sim_animator_system::<CollisionShape>
+                        .label(InjectSystem::Animate)
+                        .after(InjectSystem::Input),
+                )
+                .with_system(
+                    handle_inject
+                        .label(InjectSystem::Touch)
+                        .label(InjectSystem::Pop)
+                        .after(InjectSystem::Animate),
+                )
+                .with_system(
+                    space_to_unfair
+                        .label(InjectSystem::Pop)
+                        .after(InjectSystem::Touch),
+                )
+                .with_system(
+                    drain_water
+                        .label(InjectSystem::Drain)
+                        .after(InjectSystem::Pop),
+                )
+                .with_system(
+                    gen_new_inject
+                        .label(InjectSystem::NextWave)
+                        .after(InjectSystem::Pop),
+                ),
+        );
+    }
+}
+
+/// Cameras, sprites and the waves of a run. [`InjectPluge`] works without it, e.g. headless.
+pub struct InjectVisualsPlugin;
+
+impl Plugin for InjectVisualsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(FishWarState::Game).with_system(setup_visuals.after(Reseed)),
+        )
+        .add_system_set(SystemSet::on_exit(FishWarState::Game).with_system(clear_ripples))
+        .add_system_set(
+            SystemSet::on_update(FishWarState::Game)
+                .with_system(dress_injects)
+                .with_system(crate::start_page::sync_with_window_size),
+        )
+        // After heron's schedule, so the material shows the water of this frame's last tick.
+        .add_system_set_to_stage(
+            CoreStage::PostUpdate,
+            SystemSet::on_update(FishWarState::Game).with_system(sync_waves_material),
+        );
+        add_sim_system_set(
+            app,
+            SystemSet::on_update(FishWarState::Game)
+                .with_system(push_ripples.after(InjectSystem::Pop)),
+        );
     }
 }
+
+/// Order of the gameplay systems within a tick, fixed so that a replay takes the same path as the
+/// recorded run.
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum InjectSystem {
+    Input,
+    Animate,
+    /// The mouse touching injects, runs before the Space "Unfair Advantage".
+    Touch,
+    /// Sends [`Source`] events.
+    Pop,
+    /// Lowers [`WaterLevel`] for this frame's pops.
+    Drain,
+    /// Starts the next wave once the current one is cleared.
+    NextWave,
+}
+
 #[derive(Component)]
-struct InjecDespawn;
+pub struct InjecDespawn;
 
 #[derive(Component)]
-struct Inject;
+pub struct Inject;
+
+/// How an inject behaves, picked from the wave's `KindWeights` when it spawns.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InjectKind {
+    Normal,
+    /// Raises the water when popped.
+    Bomb,
+    /// Shrinks when touched and pops once small, lowers the water twice as much.
+    Golden,
+    /// Breaks into two smaller normal injects when popped, see [`SPLIT_FACTOR`].
+    Splitter,
+    /// Has to grow to [`HEAVY_FACTOR`] times the usual radius before it pops.
+    Heavy,
+}
+
+pub const HEAVY_FACTOR: f32 = 1.5;
+/// A golden inject pops once shrunk to this share of its spawn radius.
+pub const GOLDEN_FACTOR: f32 = 0.5;
+/// Radius of the two injects a splitter breaks into, as a share of the popped one.
+pub const SPLIT_FACTOR: f32 = 0.5;
+
+impl InjectKind {
+    /// Whether an inject of this kind and radius pops when touched.
+    pub fn is_ready(self, radius: f32, settings: &WaveSettings) -> bool {
+        match self {
+            Self::Normal | Self::Bomb | Self::Splitter => radius >= settings.enough_radius,
+            Self::Golden => radius <= settings.init_radius * GOLDEN_FACTOR,
+            Self::Heavy => radius >= settings.enough_radius * HEAVY_FACTOR,
+        }
+    }
+
+    /// Water lowered by a pop, given the wave's usual drop. Negative values raise it.
+    pub fn water_drop(self, drop: f32) -> f32 {
+        match self {
+            Self::Bomb => -drop,
+            Self::Golden => drop * 2.0,
+            Self::Normal | Self::Splitter | Self::Heavy => drop,
+        }
+    }
+
+    /// Density of the physics body, heavy injects are harder to push around.
+    pub fn density(self) -> f32 {
+        match self {
+            Self::Heavy => 3.0,
+            _ => 1.0,
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Self::Normal => Color::WHITE,
+            Self::Bomb => Color::ORANGE_RED,
+            Self::Golden => Color::GOLD,
+            Self::Splitter => Color::FUCHSIA,
+            Self::Heavy => Color::DARK_GRAY,
+        }
+    }
+}
 
 #[derive(Component)]
 struct Wall;
 
 #[derive(Component)]
-struct InjectCamera;
+pub struct InjectCamera;
 
-struct EnoughRadius(f32);
+/// Size of the play field. Follows the window, but is kept apart from it so a replay can use
+/// the size the run was recorded with.
+pub struct Arena(pub Vec2);
 
-impl Default for EnoughRadius {
+impl Default for Arena {
     fn default() -> Self {
-        Self(50.0)
+        Self(Vec2::new(1280.0, 720.0))
     }
 }
 
+/// Water left in the arena, between zero and [`FULL_WATER`]. Running dry ends the run, unless
+/// the [`GameMode`] says otherwise.
+pub struct WaterLevel(pub f32);
+
+pub const FULL_WATER: f32 = 1.0;
+
+/// Arena size the current walls were built for.
+struct WallsSize(Vec2);
+
 const DEFAULT_WALL_WIDTH: f32 = 0.1;
 
+/// Cursor speed, in pixels per second, above which the cursor stirs the water.
+const RIPPLE_CURSOR_SPEED: f32 = 2000.0;
+/// Shortest time between two cursor ripples, in seconds.
+const RIPPLE_CURSOR_INTERVAL: f32 = 0.2;
+
 fn setup(
+    mut commands: Commands,
+    mode: Res<GameMode>,
+    difficulty: Res<ActiveDifficulty>,
+    mut rng: ResMut<GameRng>,
+    arena: Res<Arena>,
+) {
+    let settings = difficulty.0.wave(1);
+    commands.insert_resource(WaveNumber(1));
+    commands.insert_resource(CurrentInject(settings.injects));
+    commands.insert_resource(WaterLevel(FULL_WATER));
+    commands.insert_resource(mode.rules());
+
+    let half_width = arena.0.x * 0.5;
+    let half_height = arena.0.y * 0.5;
+
+    commands.spawn_bundle((
+        InjecDespawn,
+        GlobalTransform::default(),
+        Transform::default(),
+        Mouse,
+        RigidBody::Sensor,
+        CollisionShape::Sphere { radius: 0.5 },
+        CollisionLayers::new(Layer::Mouse, Layer::Inject),
+    ));
+    spawn_inject(
+        &mut commands,
+        &settings,
+        settings.injects,
+        half_width - settings.init_radius,
+        half_height - settings.init_radius,
+        &mut rng.spawn,
+    );
+    commands.insert_resource(settings);
+
+    spawn_all_wall(&mut commands, arena.0.x, arena.0.y);
+    commands.insert_resource(WallsSize(arena.0));
+}
+
+fn setup_visuals(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    windows: Res<Windows>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<WavesMaterial>>,
+    arena: Res<Arena>,
+    windows: Res<Windows>,
+    mut viewport: ResMut<WavesViewport>,
+    waves_mesh: Res<WavesMesh>,
 ) {
-    commands.insert_resource(CurrentInject::default());
-    commands.insert_resource(MaxInject::default());
-    commands.insert_resource(InitRadius::default());
-    commands.insert_resource(EnoughRadius::default());
+    if let Some(window) = windows.get_primary() {
+        *viewport = WavesViewport::of(window);
+    }
 
     commands
         .spawn_bundle(OrthographicCameraBundle::new_2d())
@@ -90,43 +283,13 @@ fn setup(
         })
         .insert(InjecDespawn);
 
-    let window = windows.get_primary().unwrap();
-    let half_width = window.width() * 0.5;
-    let half_height = window.height() * 0.5;
-
-    let lens = WavesPropertiesLens {
-        start: WavesMaterial {
-            amplitude: 0.2,
-            angular_velocity: 0.8,
-            frequency: 3.,
-            color: Color::SEA_GREEN.into(),
-            time: Default::default(),
-            offset: Default::default(),
-        },
-        end: WavesMaterial {
-            amplitude: 0.15,
-            angular_velocity: 0.8,
-            frequency: 3.5,
-            color: Color::PINK.into(),
-            time: Default::default(),
-            offset: Default::default(),
-        },
-    };
-    let tween = Tween::new(
-        EaseMethod::Linear,
-        TweeningType::PingPong,
-        Duration::from_secs_f32(15.0),
-        lens,
-    );
-
     let waves = materials.add(WavesMaterial::default());
-    let window = windows.get_primary().unwrap();
 
     commands
         .spawn()
         .insert_bundle(MaterialMesh2dBundle {
             mesh: meshes
-                .add(new_waves_mesh(window.width(), window.height()))
+                .add(new_waves_mesh(arena.0.x, arena.0.y, &waves_mesh))
                 .into(),
             transform: Transform {
                 translation: Vec3::new(0., 0., 0.),
@@ -136,31 +299,9 @@ fn setup(
             material: waves.clone(),
             ..Default::default()
         })
-        .insert(AssetAnimator::new(waves, tween))
+        .insert(AssetAnimator::new(waves, waves_tween()))
         .insert(InjecDespawn)
         .insert(Wave);
-
-    commands.spawn_bundle((
-        InjecDespawn,
-        GlobalTransform::default(),
-        Transform::default(),
-        Mouse,
-        RigidBody::Sensor,
-        CollisionShape::Sphere { radius: 0.5 },
-        CollisionLayers::new(Layer::Mouse, Layer::Inject),
-    ));
-    let init_radius = InitRadius::default().0;
-
-    spawn_inject(
-        &mut commands,
-        CurrentInject::default().0,
-        init_radius,
-        half_width - init_radius,
-        half_height - init_radius,
-        &asset_server,
-    );
-
-    spawn_all_wall(&mut commands, window.width(), window.height());
 }
 
 fn spawn_all_wall(commands: &mut Commands, window_width: f32, window_helight: f32) {
@@ -192,113 +333,128 @@ fn spawn_all_wall(commands: &mut Commands, window_width: f32, window_helight: f3
     );
 }
 
-struct CurrentInject(u8);
-
-impl Default for CurrentInject {
-    fn default() -> Self {
-        Self(3)
-    }
-}
-
-struct MaxInject(u8);
-
-impl Default for MaxInject {
-    fn default() -> Self {
-        Self(4)
-    }
-}
+/// Injects of the current wave that are still on screen.
+pub struct CurrentInject(pub u8);
 
-struct InitRadius(f32);
+/// The wave currently on screen, starting from 1.
+pub struct WaveNumber(pub u8);
 
-impl Default for InitRadius {
-    fn default() -> Self {
-        Self(20.)
+fn spawn_inject(
+    commands: &mut Commands,
+    settings: &WaveSettings,
+    count: u8,
+    half_width: f32,
+    half_height: f32,
+    rng: &mut impl Rng,
+) {
+    for _ in 0..count {
+        let kind = settings.kinds.pick(rng);
+        let translation = Vec3::new(
+            rng.gen_range(-half_width..half_width),
+            rng.gen_range(-half_height..half_height),
+            1.,
+        );
+        spawn_one(
+            commands,
+            settings,
+            kind,
+            translation,
+            settings.init_radius,
+            rng,
+        );
     }
 }
 
-fn spawn_inject(
+fn spawn_one(
     commands: &mut Commands,
-    num: u8,
+    settings: &WaveSettings,
+    kind: InjectKind,
+    translation: Vec3,
     radius: f32,
-    half_width: f32,
-    half_height: f32,
-    asset_server: &AssetServer,
+    rng: &mut impl Rng,
 ) {
-    for _ in 0..num {
-        commands
-            .spawn_bundle(SpriteBundle {
-                texture: asset_server.load("icon.png"),
-                transform: Transform::from_xyz(
-                    rand::thread_rng().gen_range(-half_width..half_width),
-                    rand::thread_rng().gen_range(-half_height..half_height),
-                    1.,
-                ),
-                sprite: Sprite {
-                    custom_size: Some(Vec2::splat(radius * 2.0)),
-                    ..Default::default()
-                },
+    commands
+        .spawn_bundle((
+            Transform::from_translation(translation),
+            GlobalTransform::default(),
+        ))
+        .insert_bundle((
+            InjecDespawn,
+            Inject,
+            kind,
+            RigidBody::Dynamic,
+            CollisionShape::Sphere { radius },
+            Velocity::from_linear(Vec3::new(
+                rand_f32_for_velocity(rng, &settings.velocity),
+                rand_f32_for_velocity(rng, &settings.velocity),
+                rand_f32_for_velocity(rng, &settings.velocity),
+            ))
+            .with_angular(AxisAngle::new(Vec3::Z, rand_f32_for_angular(rng))),
+            PhysicMaterial {
+                restitution: 0.7,
+                density: kind.density(),
                 ..Default::default()
-            })
-            .insert_bundle((
-                InjecDespawn,
-                Inject,
-                RigidBody::Dynamic,
-                CollisionShape::Sphere { radius },
-                Velocity::from_linear(Vec3::new(
-                    rand_f32_for_velocity(),
-                    rand_f32_for_velocity(),
-                    rand_f32_for_velocity(),
-                ))
-                .with_angular(AxisAngle::new(Vec3::Z, rand_f32_for_angular())),
-                PhysicMaterial {
-                    restitution: 0.7,
-                    ..Default::default()
-                },
-                CollisionLayers::none()
-                    .with_group(Layer::Inject)
-                    .with_masks(&[Layer::Inject, Layer::Wall, Layer::Mouse]),
-            ));
-    }
+            },
+            CollisionLayers::none()
+                .with_group(Layer::Inject)
+                .with_masks(&[Layer::Inject, Layer::Wall, Layer::Mouse]),
+        ));
 }
 
 fn gen_new_inject(
     mut commands: Commands,
     mut current_num: ResMut<CurrentInject>,
-    mut max_num: ResMut<MaxInject>,
-    asset_server: Res<AssetServer>,
-    windows: Res<Windows>,
-    init_radois: Res<InitRadius>,
+    mut wave: ResMut<WaveNumber>,
+    mut settings: ResMut<WaveSettings>,
+    difficulty: Res<ActiveDifficulty>,
+    rules: Res<ModeRules>,
+    arena: Res<Arena>,
+    mut rng: ResMut<GameRng>,
 ) {
     if current_num.0 == 0 {
-        let window = windows.get_primary().unwrap();
-        let half_width = window.width() * 0.5 - init_radois.0;
-        let half_height = window.height() * 0.5 - init_radois.0;
-        spawn_inject(
-            &mut commands,
-            max_num.0,
-            init_radois.0,
-            half_width,
-            half_height,
-            &asset_server,
-        );
-        current_num.0 = max_num.0;
-        if let Some(res) = max_num.0.checked_add(1) {
-            max_num.0 = res;
+        if let Some(res) = wave.0.checked_add(1) {
+            wave.0 = res;
         }
+        *settings = difficulty.0.wave(wave.0);
+    } else if !rules.refill || current_num.0 >= settings.injects {
+        return;
+    }
+
+    let half_width = arena.0.x * 0.5 - settings.init_radius;
+    let half_height = arena.0.y * 0.5 - settings.init_radius;
+    spawn_inject(
+        &mut commands,
+        &settings,
+        settings.injects - current_num.0,
+        half_width,
+        half_height,
+        &mut rng.spawn,
+    );
+    current_num.0 = settings.injects;
+}
+
+/// Picks up difficulty changes made while playing, e.g. a hot reloaded preset.
+fn sync_wave_settings(
+    difficulty: Res<ActiveDifficulty>,
+    wave: Res<WaveNumber>,
+    mut settings: ResMut<WaveSettings>,
+) {
+    if difficulty.is_changed() {
+        *settings = difficulty.0.wave(wave.0);
     }
 }
 
-fn rand_f32_for_velocity() -> f32 {
-    let res = rand::thread_rng().gen_range(50.0..80.0);
-    if rand::random() {
+fn rand_f32_for_velocity(rng: &mut impl Rng, range: &Range<f32>) -> f32 {
+    let res = rng.gen_range(range.clone());
+    if rng.gen() {
         res
     } else {
         -res
     }
 }
-fn rand_f32_for_angular() -> f32 {
-    let res = rand::thread_rng().gen_range(0.3..1.2);
-    if rand::random() {
+fn rand_f32_for_angular(rng: &mut impl Rng) -> f32 {
+    let res = rng.gen_range(0.3..1.2);
+    if rng.gen() {
         res
     } else {
         -res
@@ -323,17 +479,19 @@ fn spawn_wall(commands: &mut Commands, size: Vec2, position: Transform) {
     ));
 }
 
-fn sync_with_window_size(
+fn sync_with_arena_size(
     mut commands: Commands,
-    mut resize: EventReader<WindowResized>,
+    arena: Res<Arena>,
+    mut walls_size: ResMut<WallsSize>,
     wall_query: Query<Entity, With<Wall>>,
-    mut query_inject: Query<(&mut Transform, &Sprite), With<Inject>>,
+    mut query_inject: Query<(&mut Transform, &CollisionShape), With<Inject>>,
 ) {
-    if let Some(resize) = resize.iter().last() {
-        let half_resize_width = resize.width * 0.5;
-        let half_resize_height = resize.height * 0.5;
-        for (mut transform, sprite) in query_inject.iter_mut() {
-            let radius = sprite.custom_size.unwrap().x;
+    if walls_size.0 != arena.0 {
+        walls_size.0 = arena.0;
+        let half_resize_width = arena.0.x * 0.5;
+        let half_resize_height = arena.0.y * 0.5;
+        for (mut transform, shape) in query_inject.iter_mut() {
+            let radius = sphere_radius(shape);
             let half_width = half_resize_width - radius;
             let half_height = half_resize_height - radius;
 
@@ -346,15 +504,15 @@ fn sync_with_window_size(
             }
 
             if transform.translation.y > half_height {
-                transform.translation.x = half_height;
+                transform.translation.y = half_height;
             }
 
             if transform.translation.y < -half_height {
-                transform.translation.x = -half_height;
+                transform.translation.y = -half_height;
             }
         }
 
-        spawn_all_wall(&mut commands, resize.width, resize.height);
+        spawn_all_wall(&mut commands, arena.0.x, arena.0.y);
 
         for wall in wall_query.iter() {
             commands.entity(wall).despawn();
@@ -419,7 +577,7 @@ fn screen_to_point_on_plane<W: AsRef<Windows>>(
 /// Computes the world position for a given screen position.
 /// The output will always be on the XY plane with Z at zero. It is designed for 2D, but also works with a 3D camera.
 /// For more flexibility in 3D, consider `screen_to_point_on_plane`.
-fn screen_to_point_2d<W: AsRef<Windows>>(
+pub fn screen_to_point_2d<W: AsRef<Windows>>(
     pos_screen: Vec2,
     windows: W,
     camera: &Camera,
@@ -468,27 +626,25 @@ impl Plane {
 #[derive(Component)]
 struct Mouse;
 
-fn sync_mouse_postion(
-    windows: Res<Windows>,
-    mut mouse_query: Query<&mut Transform, With<Mouse>>,
-    camera: Query<(&Camera, &GlobalTransform), With<InjectCamera>>,
-) {
-    if let Some(position) = windows.get_primary().and_then(|w| w.cursor_position()) {
-        let (camera, global_transform) = camera.get_single().unwrap();
-        if let Some(point) = screen_to_point_2d(position, windows, camera, global_transform) {
-            let mut transform = mouse_query.get_single_mut().unwrap();
-            *transform = Transform::from_translation(point);
-        }
+fn sync_mouse_postion(input: Res<FrameInput>, mut mouse_query: Query<&mut Transform, With<Mouse>>) {
+    if let Some(point) = input.cursor {
+        let mut transform = mouse_query.get_single_mut().unwrap();
+        *transform = Transform::from_translation(point.extend(0.0));
     }
 }
 
 fn handle_inject(
-    mut commands: Commands,
     mut events: EventReader<CollisionEvent>,
-    inject_query: Query<(&Transform, &CollisionShape, Option<&Animator<Transform>>), With<Inject>>,
-    enough: Res<EnoughRadius>,
-    mut current: ResMut<CurrentInject>,
-    mut source: EventWriter<Source>,
+    inject_query: Query<
+        (
+            &Transform,
+            &CollisionShape,
+            &InjectKind,
+            Option<&SimAnimator<Transform>>,
+        ),
+        With<Inject>,
+    >,
+    mut touch: InjectTouch,
 ) {
     for inject_entity in events
         .iter()
@@ -507,56 +663,110 @@ fn handle_inject(
             }
         })
     {
-        if let Ok((transform, shape, op_t)) = inject_query.get(inject_entity) {
-            if is_shape_enough(shape, enough.0) {
-                if let Some(res) = current.0.checked_sub(1) {
-                    current.0 = res;
-                    source.send(Source);
-                    commands.entity(inject_entity).despawn();
+        if let Ok((transform, shape, kind, op_t)) = inject_query.get(inject_entity) {
+            touch.touch(inject_entity, transform, shape, *kind, op_t);
+        }
+    }
+}
+
+/// What touching an inject does, shared by the mouse and the Space "Unfair Advantage".
+#[derive(SystemParam)]
+struct InjectTouch<'w, 's> {
+    commands: Commands<'w, 's>,
+    settings: Res<'w, WaveSettings>,
+    current: ResMut<'w, CurrentInject>,
+    source: EventWriter<'w, 's, Source>,
+    rng: ResMut<'w, GameRng>,
+}
+
+impl<'w, 's> InjectTouch<'w, 's> {
+    /// Pops the inject if it is ready, grows (or shrinks) it otherwise.
+    fn touch(
+        &mut self,
+        entity: Entity,
+        transform: &Transform,
+        shape: &CollisionShape,
+        kind: InjectKind,
+        animator: Option<&SimAnimator<Transform>>,
+    ) {
+        let radius = sphere_radius(shape);
+        if kind.is_ready(radius, &self.settings) {
+            if let Some(res) = self.current.0.checked_sub(1) {
+                self.current.0 = res;
+                self.source.send(Source {
+                    radius,
+                    kind,
+                    position: transform.translation.truncate(),
+                });
+                self.commands.entity(entity).despawn();
+                if kind == InjectKind::Splitter {
+                    self.split(transform.translation, radius);
                 }
-                continue;
             }
+            return;
+        }
 
-            if let Some(t) = op_t {
-                if t.progress() < 1.0 {
-                    continue;
-                }
+        if let Some(t) = animator {
+            if t.progress() < 1.0 {
+                return;
             }
+        }
 
-            let rand = rand::thread_rng().gen_range(1.5..3.6);
-            let start = transform.scale;
-            let end = start * rand;
-            let tween = Tween::new(
-                EaseFunction::QuadraticInOut,
-                TweeningType::Once,
-                Duration::from_secs_f32(1.0),
-                TransformScaleLens { start, end },
-            );
+        let growth = self.rng.growth.gen_range(self.settings.growth.clone());
+        let rand = if kind == InjectKind::Golden {
+            1.0 / growth
+        } else {
+            growth
+        };
+        let start = transform.scale;
+        let end = start * rand;
+        let tween = Tween::new(
+            EaseFunction::QuadraticInOut,
+            TweeningType::Once,
+            Duration::from_secs_f32(1.0),
+            TransformScaleLens { start, end },
+        );
+
+        let collision_tween = Tween::new(
+            EaseFunction::QuadraticInOut,
+            TweeningType::Once,
+            Duration::from_secs_f32(1.0),
+            CollisionShapeSacleLens {
+                start: radius,
+                end: radius * rand,
+            },
+        );
+        self.commands
+            .entity(entity)
+            .insert(SimAnimator::new(collision_tween))
+            .insert(SimAnimator::new(tween));
+    }
 
-            if let CollisionShape::Sphere { radius } = shape {
-                let collision_tween = Tween::new(
-                    EaseFunction::QuadraticInOut,
-                    TweeningType::Once,
-                    Duration::from_secs_f32(1.0),
-                    CollisionShapeSacleLens {
-                        start: *radius,
-                        end: *radius * rand,
-                    },
+    /// Replaces a popped splitter with two smaller normal injects, side by side where it was.
+    fn split(&mut self, at: Vec3, radius: f32) {
+        if let Some(res) = self.current.0.checked_add(2) {
+            self.current.0 = res;
+            let radius = radius * SPLIT_FACTOR;
+            let offset = Vec3::X * radius;
+            for translation in [at - offset, at + offset] {
+                spawn_one(
+                    &mut self.commands,
+                    &self.settings,
+                    InjectKind::Normal,
+                    translation,
+                    radius,
+                    &mut self.rng.spawn,
                 );
-                commands
-                    .entity(inject_entity)
-                    .insert(Animator::new(collision_tween))
-                    .insert(Animator::new(tween));
             }
         }
     }
 }
 
-fn is_shape_enough(shape: &CollisionShape, enough: f32) -> bool {
+fn sphere_radius(shape: &CollisionShape) -> f32 {
     if let CollisionShape::Sphere { radius } = shape {
-        return *radius >= enough;
+        return *radius;
     }
-    false
+    0.0
 }
 
 fn is_mouse(layers: CollisionLayers) -> bool {
@@ -587,96 +797,140 @@ impl Lens<CollisionShape> for CollisionShapeSacleLens {
     }
 }
 
-fn sync_with_time(
-    mut materials: ResMut<Assets<WavesMaterial>>,
-    query_waves: Query<&Handle<WavesMaterial>>,
-    time: Res<Time>,
+fn drain_water(
+    mut water: ResMut<WaterLevel>,
     mut source: EventReader<Source>,
     mut game_state: ResMut<State<FishWarState>>,
+    settings: Res<WaveSettings>,
+    rules: Res<ModeRules>,
+    mut sim_time: ResMut<SimTime>,
+    mut rng: ResMut<GameRng>,
 ) {
-    let handle = query_waves.get_single().unwrap();
-    let waves = materials.get_mut(handle).unwrap();
-
-    waves.time = time.seconds_since_startup() as f32;
-
-    if waves.offset <= 0.0 {
+    let drowned = rules.drown && water.0 <= 0.0;
+    let timed_out = rules.time_left(sim_time.seconds()) == Some(0.0);
+    if drowned || timed_out {
+        sim_time.stop();
         if let Err(e) = game_state.set(FishWarState::GameOver) {
             warn!("set state error: {:?}", e);
         };
     }
 
-    for _ in source.iter() {
-        waves.offset -= rand::thread_rng().gen_range(0.01..0.02);
+    for Source { kind, .. } in source.iter() {
+        water.0 -= kind.water_drop(rng.water.gen_range(settings.water_drop.clone()));
+    }
+    water.0 = (water.0 + rules.water_recovery * SIM_STEP.as_secs_f32()).min(FULL_WATER);
+    if !rules.drown {
+        water.0 = water.0.max(0.0);
     }
 }
 
-fn space_to_unfair(
+/// Only touches the material when the water moved, `get_mut` makes it prepare again.
+fn sync_waves_material(
+    mut materials: ResMut<Assets<WavesMaterial>>,
+    query_waves: Query<&Handle<WavesMaterial>, With<Wave>>,
+    water: Res<WaterLevel>,
+) {
+    for handle in query_waves.iter() {
+        if materials.get(handle).map(|waves| waves.offset) != Some(water.0) {
+            if let Some(waves) = materials.get_mut(handle) {
+                waves.offset = water.0;
+            }
+        }
+    }
+}
+
+/// Last cursor ripple, kept by [`push_ripples`].
+#[derive(Default)]
+struct CursorRipple {
+    cursor: Option<Vec2>,
+    start: f32,
+}
+
+/// Moves the waves on by one tick and stirs the water where injects pop and where the cursor
+/// moves fast.
+fn push_ripples(
+    mut ripples: ResMut<Ripples>,
+    mut source: EventReader<Source>,
+    input: Res<FrameInput>,
+    arena: Res<Arena>,
+    mut waves_time: ResMut<WavesTime>,
+    mut last: Local<CursorRipple>,
+) {
+    waves_time.0 += SIM_STEP.as_secs_f32();
+    let now = waves_time.0;
+    for pop in source.iter() {
+        let strength = (pop.radius / arena.0.y * 0.3).min(0.05);
+        ripples.push(world_to_uv(pop.position, arena.0), now, strength);
+    }
+
+    let previous = std::mem::replace(&mut last.cursor, input.cursor);
+    if let (Some(previous), Some(cursor)) = (previous, input.cursor) {
+        // The cursor only moves once per frame, the frame's first tick sees all of it.
+        let speed = cursor.distance(previous) / (f32::from(input.ticks) * SIM_STEP.as_secs_f32());
+        if speed > RIPPLE_CURSOR_SPEED && now - last.start >= RIPPLE_CURSOR_INTERVAL {
+            last.start = now;
+            let strength = (speed / RIPPLE_CURSOR_SPEED * 0.01).min(0.03);
+            ripples.push(world_to_uv(cursor, arena.0), now, strength);
+        }
+    }
+}
+
+/// Gives new injects their sprite, tinted by [`InjectKind`].
+fn dress_injects(
     mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    query: Query<(Entity, &CollisionShape, &InjectKind), Added<Inject>>,
+) {
+    for (entity, shape, kind) in query.iter() {
+        commands.entity(entity).insert_bundle((
+            Sprite {
+                color: kind.color(),
+                custom_size: Some(Vec2::splat(sphere_radius(shape) * 2.0)),
+                ..Default::default()
+            },
+            asset_server.load::<Image, _>("icon.png"),
+            Visibility::default(),
+        ));
+    }
+}
+
+fn space_to_unfair(
     inject_query: Query<
         (
             Entity,
             &Transform,
             &CollisionShape,
-            Option<&Animator<Transform>>,
+            &InjectKind,
+            Option<&SimAnimator<Transform>>,
         ),
         With<Inject>,
     >,
-    enough: Res<EnoughRadius>,
-    mut current: ResMut<CurrentInject>,
-    mut source: EventWriter<Source>,
-    keyboard_input: Res<Input<KeyCode>>,
+    input: Res<FrameInput>,
+    mut touch: InjectTouch,
 ) {
-    if keyboard_input.pressed(KeyCode::Space) {
-        for (inject_entity, transform, shape, op_t) in inject_query.iter() {
-            if is_shape_enough(shape, enough.0) {
-                if let Some(res) = current.0.checked_sub(1) {
-                    current.0 = res;
-                    source.send(Source);
-                    commands.entity(inject_entity).despawn();
-                }
-                continue;
-            }
-
-            if let Some(t) = op_t {
-                if t.progress() < 1.0 {
-                    continue;
-                }
-            }
-
-            let rand = rand::thread_rng().gen_range(1.5..3.6);
-            let start = transform.scale;
-            let end = start * rand;
-            let tween = Tween::new(
-                EaseFunction::QuadraticInOut,
-                TweeningType::Once,
-                Duration::from_secs_f32(1.0),
-                TransformScaleLens { start, end },
-            );
-
-            if let CollisionShape::Sphere { radius } = shape {
-                let collision_tween = Tween::new(
-                    EaseFunction::QuadraticInOut,
-                    TweeningType::Once,
-                    Duration::from_secs_f32(1.0),
-                    CollisionShapeSacleLens {
-                        start: *radius,
-                        end: *radius * rand,
-                    },
-                );
-                commands
-                    .entity(inject_entity)
-                    .insert(Animator::new(collision_tween))
-                    .insert(Animator::new(tween));
-            }
+    if input.unfair {
+        for (inject_entity, transform, shape, kind, op_t) in inject_query.iter() {
+            touch.touch(inject_entity, transform, shape, *kind, op_t);
         }
     }
 }
 
-struct Source;
+/// Sent every time an inject is popped.
+pub struct Source {
+    pub radius: f32,
+    pub kind: InjectKind,
+    /// Where the inject was, in world coordinates.
+    pub position: Vec2,
+}
+
+fn clear_ripples(mut ripples: ResMut<Ripples>) {
+    ripples.clear();
+}
 
 fn remove_resource(mut commands: Commands) {
     commands.remove_resource::<CurrentInject>();
-    commands.remove_resource::<MaxInject>();
-    commands.remove_resource::<InitRadius>();
-    commands.remove_resource::<EnoughRadius>();
+    commands.remove_resource::<WaveNumber>();
+    commands.remove_resource::<WaveSettings>();
+    commands.remove_resource::<WallsSize>();
+    commands.remove_resource::<WaterLevel>();
 }
@@ -0,0 +1,18 @@
+#![allow(clippy::type_complexity)]
+
+pub mod difficulty;
+pub mod game_over;
+pub mod game_state;
+pub mod highscore;
+pub mod inject;
+pub mod mode;
+pub mod pause;
+pub mod replay;
+pub mod rng;
+pub mod score;
+pub mod shader_paths;
+pub mod sim;
+pub mod start_page;
+pub mod utils;
+pub mod victory;
+pub mod waves;
@@ -1,31 +1,60 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
-#![allow(clippy::type_complexity)]
 
 use bevy::{prelude::App, DefaultPlugins};
 use bevy_tweening::TweeningPlugin;
-use game_over::GameOverPlugin;
-use inject::InjectPluge;
-use start_page::StartPagePlugin;
-use waves::WavesPlugin;
-
-mod game_over;
-mod game_state;
-mod inject;
-mod start_page;
-mod utils;
-mod waves;
+use inject::{
+    difficulty::DifficultyPlugin,
+    game_over::GameOverPlugin,
+    game_state::FishWarState,
+    highscore::HighScorePlugin,
+    inject::{InjectPluge, InjectVisualsPlugin},
+    pause::PausePlugin,
+    replay::{InputSource, ReplayPlugin},
+    rng::RngPlugin,
+    score::ScorePlugin,
+    sim::SimPlugin,
+    start_page::StartPagePlugin,
+    victory::VictoryPlugin,
+    waves::WavesPlugin,
+};
 
 fn main() {
     let mut app = App::new();
 
+    // Reload changed assets, e.g. difficulty presets or the shaders rebuilt by
+    // `cargo xtask shader --watch`, without restarting the game.
+    #[cfg(feature = "dev")]
+    app.insert_resource(bevy::asset::AssetServerSettings {
+        watch_for_changes: true,
+        ..Default::default()
+    });
+
     app.add_plugins(DefaultPlugins)
         .add_plugin(TweeningPlugin)
         .add_plugin(StartPagePlugin)
+        .add_plugin(RngPlugin)
+        .add_plugin(SimPlugin)
+        .add_plugin(ReplayPlugin)
+        .add_plugin(DifficultyPlugin)
         .add_plugin(InjectPluge)
+        .add_plugin(InjectVisualsPlugin)
+        .add_plugin(PausePlugin)
+        .add_plugin(ScorePlugin)
         .add_plugin(WavesPlugin)
         .add_plugin(GameOverPlugin)
-        .add_plugin(heron::prelude::PhysicsPlugin::default())
-        .add_state(game_state::FishWarState::Menu);
+        .add_plugin(HighScorePlugin)
+        .add_plugin(VictoryPlugin);
+
+    // `--replay <file>` skips the menu and plays the recorded run right away.
+    let replaying = app
+        .world
+        .get_resource::<InputSource>()
+        .map_or(false, InputSource::is_replay);
+    app.add_state(if replaying {
+        FishWarState::Game
+    } else {
+        FishWarState::Menu
+    });
 
     #[cfg(feature = "dev")]
     app.add_plugin(bevy_inspector_egui::WorldInspectorPlugin::new())
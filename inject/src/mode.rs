@@ -0,0 +1,87 @@
+use codec::{Decode, Encode, MaxEncodedLen};
+
+/// Rule set of the next run, picked on the start page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, MaxEncodedLen)]
+pub enum GameMode {
+    /// Play until the water runs out.
+    Classic,
+    /// Pop as many injects as possible before the time runs out.
+    TimeAttack,
+    /// Classic, but the water slowly comes back.
+    Endless,
+    /// No game over, just popping.
+    Zen,
+}
+
+impl Default for GameMode {
+    fn default() -> Self {
+        Self::Classic
+    }
+}
+
+impl GameMode {
+    pub const ALL: [Self; 4] = [Self::Classic, Self::TimeAttack, Self::Endless, Self::Zen];
+
+    pub fn next(self) -> Self {
+        Self::ALL[(self as usize + 1) % Self::ALL.len()]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Classic => "Classic",
+            Self::TimeAttack => "Time Attack",
+            Self::Endless => "Endless",
+            Self::Zen => "Zen",
+        }
+    }
+
+    pub fn rules(self) -> ModeRules {
+        let classic = ModeRules {
+            mode: self,
+            drown: true,
+            water_recovery: 0.0,
+            time_limit: None,
+            refill: false,
+        };
+
+        match self {
+            Self::Classic => classic,
+            Self::TimeAttack => ModeRules {
+                drown: false,
+                time_limit: Some(90.0),
+                refill: true,
+                ..classic
+            },
+            Self::Endless => ModeRules {
+                water_recovery: 0.004,
+                ..classic
+            },
+            Self::Zen => ModeRules {
+                drown: false,
+                ..classic
+            },
+        }
+    }
+}
+
+/// What the [`GameMode`] of the current (or last finished) run changes in the `InjectPluge`
+/// loop, fixed when the run starts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModeRules {
+    pub mode: GameMode,
+    /// Running out of water ends the run.
+    pub drown: bool,
+    /// Water regained every second.
+    pub water_recovery: f32,
+    /// Run length in seconds.
+    pub time_limit: Option<f64>,
+    /// Popped injects are replaced right away, instead of once the whole wave is cleared.
+    pub refill: bool,
+}
+
+impl ModeRules {
+    /// Seconds left of a limited run, given the seconds played.
+    pub fn time_left(&self, played: f64) -> Option<f64> {
+        self.time_limit.map(|limit| (limit - played).max(0.0))
+    }
+}
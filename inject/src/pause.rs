@@ -0,0 +1,216 @@
+use bevy::{prelude::*, ui::UiSystem};
+use bevy_tweening::{AnimatorState, AssetAnimator};
+
+use crate::{
+    game_state::FishWarState,
+    replay::{FrameInput, InputSource, ReadInput, RecordInput},
+    start_page::{HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON},
+    utils::despawn_screen,
+    waves::WavesMaterial,
+};
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(
+            CoreStage::PreUpdate,
+            click_pause_buttons
+                .after(UiSystem::Focus)
+                .after(ReadInput)
+                .before(RecordInput),
+        )
+        .add_system_set(SystemSet::on_update(FishWarState::Game).with_system(pause_game))
+        .add_system_set(
+            SystemSet::on_enter(FishWarState::Paused)
+                .with_system(setup)
+                .with_system(freeze),
+        )
+        .add_system_set(
+            SystemSet::on_update(FishWarState::Paused)
+                .with_system(button_colors)
+                .with_system(pause_menu),
+        )
+        .add_system_set(
+            SystemSet::on_exit(FishWarState::Paused)
+                .with_system(despawn_screen::<PauseDespawn>)
+                .with_system(unfreeze),
+        );
+    }
+}
+
+#[derive(Component)]
+struct PauseDespawn;
+
+#[derive(Component, Clone, Copy)]
+enum PauseButton {
+    Resume,
+    Restart,
+    MainMenu,
+}
+
+impl PauseButton {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Resume => "Resume",
+            Self::Restart => "Restart",
+            Self::MainMenu => "Main Menu",
+        }
+    }
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/rock-salt-regular.ttf");
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+            ..Default::default()
+        })
+        .insert(PauseDespawn)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                style: Style {
+                    margin: Rect::all(Val::Px(20.0)),
+                    ..Default::default()
+                },
+                text: Text::with_section(
+                    "Paused",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 60.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+
+            for button in [
+                PauseButton::Resume,
+                PauseButton::Restart,
+                PauseButton::MainMenu,
+            ] {
+                parent
+                    .spawn_bundle(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(220.0), Val::Px(65.0)),
+                            margin: Rect::all(Val::Px(10.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        color: NORMAL_BUTTON.into(),
+                        ..Default::default()
+                    })
+                    .insert(button)
+                    .with_children(|parent| {
+                        parent.spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                button.label(),
+                                TextStyle {
+                                    font: font.clone(),
+                                    font_size: 30.0,
+                                    color: Color::rgb(0.9, 0.9, 0.9),
+                                },
+                                Default::default(),
+                            ),
+                            ..Default::default()
+                        });
+                    });
+            }
+        });
+}
+
+/// Gameplay and physics only tick in `FishWarState::Game` and stop by themselves, the waves'
+/// color tween needs to be told.
+fn freeze(mut animators: Query<&mut AssetAnimator<WavesMaterial>>) {
+    for mut animator in animators.iter_mut() {
+        animator.state = AnimatorState::Paused;
+    }
+}
+
+fn unfreeze(mut animators: Query<&mut AssetAnimator<WavesMaterial>>) {
+    for mut animator in animators.iter_mut() {
+        animator.state = AnimatorState::Playing;
+    }
+}
+
+/// Takes the press it acted on: after a state change Bevy reruns `Update` in the same frame, and
+/// `pause_menu` would see it too and resume right away.
+fn pause_game(mut input: ResMut<FrameInput>, mut game_state: ResMut<State<FishWarState>>) {
+    if input.pause || input.back {
+        input.pause = false;
+        input.back = false;
+        if let Err(e) = game_state.push(FishWarState::Paused) {
+            warn!("push state error: {:?}", e);
+        }
+    }
+}
+
+/// Turns clicks into [`FrameInput`], so they are recorded like key presses.
+fn click_pause_buttons(
+    source: Res<InputSource>,
+    mut input: ResMut<FrameInput>,
+    interaction_query: Query<(&Interaction, &PauseButton), Changed<Interaction>>,
+) {
+    if source.is_replay() {
+        return;
+    }
+
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            match button {
+                PauseButton::Resume => input.pause = true,
+                PauseButton::Restart => input.restart = true,
+                PauseButton::MainMenu => input.menu = true,
+            }
+        }
+    }
+}
+
+fn button_colors(
+    mut interaction_query: Query<
+        (&Interaction, &mut UiColor),
+        (Changed<Interaction>, With<PauseButton>),
+    >,
+) {
+    for (interaction, mut color) in interaction_query.iter_mut() {
+        color.0 = match *interaction {
+            Interaction::Clicked => PRESSED_BUTTON,
+            Interaction::Hovered => HOVERED_BUTTON,
+            Interaction::None => NORMAL_BUTTON,
+        };
+    }
+}
+
+/// `replace` unwinds the whole stack, so the paused run is cleaned up by `on_exit(Game)`.
+///
+/// Takes the press it acted on, like [`pause_game`].
+fn pause_menu(mut input: ResMut<FrameInput>, mut game_state: ResMut<State<FishWarState>>) {
+    let result = if input.pause || input.back {
+        input.pause = false;
+        input.back = false;
+        game_state.pop()
+    } else if input.restart {
+        input.restart = false;
+        game_state.replace(FishWarState::Game)
+    } else if input.menu {
+        input.menu = false;
+        game_state.replace(FishWarState::Menu)
+    } else {
+        return;
+    };
+
+    if let Err(e) = result {
+        warn!("set state error: {:?}", e);
+    }
+}
@@ -0,0 +1,528 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{ensure, Result};
+use bevy::{app::AppExit, input::InputSystem, prelude::*, window::WindowCloseRequested};
+use codec::{Decode, Encode};
+
+use crate::{
+    difficulty::{ActiveDifficulty, Difficulty},
+    game_state::FishWarState,
+    inject::{screen_to_point_2d, Arena, InjectCamera},
+    mode::GameMode,
+    rng::{GameRng, Reseed, SeedArg},
+    sim::SimClock,
+    victory::WinCondition,
+};
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(InputSource::from_args())
+            .init_resource::<FrameInput>()
+            .init_resource::<Recorder>()
+            .init_resource::<SavedSettings>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                read_input.label(ReadInput).after(InputSystem),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                record_input.label(RecordInput).after(ReadInput),
+            )
+            .add_system_to_stage(CoreStage::PreUpdate, restore_settings.after(ReadInput))
+            .add_system_to_stage(CoreStage::Last, save_on_exit)
+            .add_system_set(
+                SystemSet::on_enter(FishWarState::Game)
+                    .with_system(start_replay.label(StartReplay).before(Reseed))
+                    .with_system(restore_settings.after(StartReplay).before(Reseed))
+                    .with_system(start_recording.after(Reseed)),
+            )
+            .add_system_set(SystemSet::on_enter(FishWarState::GameOver).with_system(save_recording))
+            .add_system_set(SystemSet::on_exit(FishWarState::GameOver).with_system(stop_recording))
+            .add_system_set(SystemSet::on_enter(FishWarState::Victory).with_system(save_recording))
+            .add_system_set(SystemSet::on_exit(FishWarState::Victory).with_system(stop_recording))
+            .add_system_set(
+                SystemSet::on_enter(FishWarState::Menu)
+                    .with_system(stop_recording)
+                    .with_system(stop_replay),
+            );
+    }
+}
+
+/// Fills [`FrameInput`] in `CoreStage::PreUpdate`.
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReadInput;
+
+/// Records [`FrameInput`] in `CoreStage::PreUpdate`, changes made after it are lost in a replay.
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RecordInput;
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct StartReplay;
+
+/// Everything the player did in one frame. Gameplay reads this instead of the devices, so a
+/// recorded run can be fed back in.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct FrameInput {
+    /// Simulation ticks the frame runs, from the wall clock in a live `FishWarState::Game`.
+    pub ticks: u8,
+    /// Cursor position in world space.
+    pub cursor: Option<Vec2>,
+    /// Window size, gameplay reads it through [`Arena`].
+    pub arena: Vec2,
+    /// Space, the "Unfair Advantage", is held.
+    pub unfair: bool,
+    /// M was pressed.
+    pub menu: bool,
+    /// G was pressed.
+    pub restart: bool,
+    /// Q was pressed.
+    pub quit: bool,
+    /// P was pressed, or Resume clicked.
+    pub pause: bool,
+    /// Escape was pressed, it pauses and resumes a run and quits from the screens after it.
+    pub back: bool,
+}
+
+const UNFAIR: u8 = 1;
+const MENU: u8 = 1 << 1;
+const RESTART: u8 = 1 << 2;
+const QUIT: u8 = 1 << 3;
+const PAUSE: u8 = 1 << 4;
+const BACK: u8 = 1 << 5;
+
+/// Bump this whenever [`RunLog`] changes.
+const VERSION: u8 = 4;
+
+/// A recorded run, from entering `FishWarState::Game` to leaving the screen that ended it.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct RunLog {
+    seed: u64,
+    /// `ActiveDifficulty` the run was played with, as RON.
+    difficulty: String,
+    win: WinCondition,
+    mode: GameMode,
+    arena: [u32; 2],
+    /// One entry per frame, identical neighbouring frames are merged.
+    frames: Vec<RepeatedFrame>,
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+struct RepeatedFrame {
+    #[codec(compact)]
+    repeat: u32,
+    frame: Frame,
+}
+
+/// [`FrameInput`] as stored in a [`RunLog`], floats are kept as bits so they survive exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Encode, Decode)]
+struct Frame {
+    ticks: u8,
+    cursor: Option<[u32; 2]>,
+    arena: [u32; 2],
+    keys: u8,
+}
+
+fn to_bits(v: Vec2) -> [u32; 2] {
+    [v.x.to_bits(), v.y.to_bits()]
+}
+
+fn from_bits([x, y]: [u32; 2]) -> Vec2 {
+    Vec2::new(f32::from_bits(x), f32::from_bits(y))
+}
+
+impl From<&FrameInput> for Frame {
+    fn from(input: &FrameInput) -> Self {
+        let keys = [
+            (input.unfair, UNFAIR),
+            (input.menu, MENU),
+            (input.restart, RESTART),
+            (input.quit, QUIT),
+            (input.pause, PAUSE),
+            (input.back, BACK),
+        ]
+        .iter()
+        .filter(|(pressed, _)| *pressed)
+        .fold(0, |keys, (_, key)| keys | key);
+
+        Self {
+            ticks: input.ticks,
+            cursor: input.cursor.map(to_bits),
+            arena: to_bits(input.arena),
+            keys,
+        }
+    }
+}
+
+impl From<&Frame> for FrameInput {
+    fn from(frame: &Frame) -> Self {
+        Self {
+            ticks: frame.ticks,
+            cursor: frame.cursor.map(from_bits),
+            arena: from_bits(frame.arena),
+            unfair: frame.keys & UNFAIR != 0,
+            menu: frame.keys & MENU != 0,
+            restart: frame.keys & RESTART != 0,
+            quit: frame.keys & QUIT != 0,
+            pause: frame.keys & PAUSE != 0,
+            back: frame.keys & BACK != 0,
+        }
+    }
+}
+
+impl RunLog {
+    /// An empty log for a run about to start with these settings.
+    pub fn new(
+        seed: u64,
+        difficulty: &Difficulty,
+        win: WinCondition,
+        mode: GameMode,
+        arena: Vec2,
+    ) -> Result<Self> {
+        Ok(Self {
+            seed,
+            difficulty: ron::to_string(difficulty)?,
+            win,
+            mode,
+            arena: to_bits(arena),
+            frames: Vec::new(),
+        })
+    }
+
+    pub fn push(&mut self, input: &FrameInput) {
+        let frame = Frame::from(input);
+        match self.frames.last_mut() {
+            Some(last) if last.frame == frame => last.repeat += 1,
+            _ => self.frames.push(RepeatedFrame { repeat: 1, frame }),
+        }
+    }
+
+    /// A file named after the time the run started, so runs with the same seed are all kept.
+    fn new_path(&self) -> Option<PathBuf> {
+        let dir = dirs::config_dir()?;
+        let started = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+        Some(dir.join("fishwar").join("replays").join(format!(
+            "{}-{}.replay",
+            started.as_millis(),
+            self.seed
+        )))
+    }
+
+    /// Every recorded frame in play order, merged frames expanded again.
+    pub fn inputs(&self) -> impl Iterator<Item = FrameInput> + '_ {
+        self.frames.iter().flat_map(|repeated| {
+            std::iter::repeat(FrameInput::from(&repeated.frame)).take(repeated.repeat as usize)
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = VERSION.encode();
+        self.encode_to(&mut bytes);
+        bytes
+    }
+
+    /// Reads a saved log, rejecting other versions.
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self> {
+        let input = &mut bytes;
+        let version = u8::decode(input)?;
+        ensure!(
+            version == VERSION,
+            "version {}, expected {}",
+            version,
+            VERSION
+        );
+
+        let log = Self::decode(input)?;
+        ensure!(input.is_empty(), "{} trailing bytes", input.len());
+        Ok(log)
+    }
+
+    fn load(path: &Path) -> Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+}
+
+/// Where [`FrameInput`] comes from, picked with `--replay <file>`.
+pub enum InputSource {
+    Live,
+    Replay(Replay),
+}
+
+pub struct Replay {
+    log: RunLog,
+    /// Index into `log.frames` and how often that entry was played already.
+    position: (usize, u32),
+    /// Set once `FishWarState::Game` is entered, the replay is fed from the next frame on.
+    started: bool,
+}
+
+impl Replay {
+    fn next_frame(&mut self) -> Option<FrameInput> {
+        let (index, played) = &mut self.position;
+        let frame = self.log.frames.get(*index)?;
+        *played += 1;
+        if *played >= frame.repeat {
+            *index += 1;
+            *played = 0;
+        }
+        Some(FrameInput::from(&frame.frame))
+    }
+}
+
+impl InputSource {
+    fn from_args() -> Self {
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            if arg == "--replay" {
+                let path = match args.next() {
+                    Some(path) => PathBuf::from(path),
+                    None => {
+                        warn!("--replay needs a file");
+                        return Self::Live;
+                    }
+                };
+
+                return match RunLog::load(&path) {
+                    Ok(log) => {
+                        info!("replaying {:?}, seed {}", path, log.seed);
+                        Self::Replay(Replay {
+                            log,
+                            position: (0, 0),
+                            started: false,
+                        })
+                    }
+                    Err(e) => {
+                        error!("load replay {:?} error: {:#}", path, e);
+                        Self::Live
+                    }
+                };
+            }
+        }
+        Self::Live
+    }
+
+    pub fn is_replay(&self) -> bool {
+        matches!(self, Self::Replay(_))
+    }
+}
+
+/// Settings a replay replaced with the recorded ones, put back once it ends.
+#[derive(Default)]
+struct SavedSettings(Option<Settings>);
+
+struct Settings {
+    seed: Option<u64>,
+    difficulty: Difficulty,
+    win: WinCondition,
+    mode: GameMode,
+    arena: Vec2,
+}
+
+/// The run being recorded, if any, and the file it is saved to.
+#[derive(Default)]
+struct Recorder(Option<(RunLog, PathBuf)>);
+
+impl Recorder {
+    fn save(&self) {
+        if let Some((log, path)) = &self.0 {
+            match log.save(path) {
+                Ok(()) => info!("run recorded to {:?}", path),
+                Err(e) => warn!("save replay error: {:?}", e),
+            }
+        }
+    }
+}
+
+fn read_input(
+    mut input: ResMut<FrameInput>,
+    mut source: ResMut<InputSource>,
+    mut arena: ResMut<Arena>,
+    mut clock: Local<SimClock>,
+    time: Res<Time>,
+    state: Res<State<FishWarState>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    windows: Res<Windows>,
+    camera: Query<(&Camera, &GlobalTransform), With<InjectCamera>>,
+) {
+    match &mut *source {
+        InputSource::Live => {
+            let window = windows.get_primary();
+            let ticks = if state.current() == &FishWarState::Game {
+                clock.advance(time.delta())
+            } else {
+                clock.reset();
+                0
+            };
+            *input = FrameInput {
+                ticks,
+                cursor: window
+                    .and_then(|window| window.cursor_position())
+                    .and_then(|position| {
+                        let (camera, transform) = camera.get_single().ok()?;
+                        screen_to_point_2d(position, &windows, camera, transform)
+                    })
+                    .map(|point| point.truncate()),
+                arena: window.map_or(arena.0, |window| Vec2::new(window.width(), window.height())),
+                unfair: keyboard_input.pressed(KeyCode::Space),
+                menu: keyboard_input.just_pressed(KeyCode::M),
+                restart: keyboard_input.just_pressed(KeyCode::G),
+                quit: keyboard_input.just_pressed(KeyCode::Q),
+                pause: keyboard_input.just_pressed(KeyCode::P),
+                back: keyboard_input.just_pressed(KeyCode::Escape),
+            };
+        }
+        InputSource::Replay(replay) if replay.started => match replay.next_frame() {
+            Some(frame) => *input = frame,
+            None => {
+                info!("replay finished");
+                *source = InputSource::Live;
+                return;
+            }
+        },
+        InputSource::Replay(_) => return,
+    }
+
+    if arena.0 != input.arena {
+        arena.0 = input.arena;
+    }
+}
+
+fn record_input(mut recorder: ResMut<Recorder>, input: Res<FrameInput>) {
+    if let Some((log, _)) = &mut recorder.0 {
+        log.push(&input);
+    }
+}
+
+/// Closing the game ends it without leaving the screen that ended the run.
+fn save_on_exit(
+    recorder: Res<Recorder>,
+    mut exit: EventReader<AppExit>,
+    mut close: EventReader<WindowCloseRequested>,
+) {
+    if exit.iter().count() + close.iter().count() > 0 {
+        recorder.save();
+    }
+}
+
+/// Starts feeding the replay with the recorded settings, and ends it when the replayed run
+/// restarts the game. The player's settings are kept in [`SavedSettings`] meanwhile.
+///
+/// Also clears the input of this frame, it is read before the run started and is not recorded.
+fn start_replay(
+    mut source: ResMut<InputSource>,
+    mut input: ResMut<FrameInput>,
+    mut saved: ResMut<SavedSettings>,
+    mut seed: ResMut<SeedArg>,
+    mut difficulty: ResMut<ActiveDifficulty>,
+    mut win: ResMut<WinCondition>,
+    mut mode: ResMut<GameMode>,
+    mut arena: ResMut<Arena>,
+) {
+    *input = FrameInput {
+        arena: input.arena,
+        ..Default::default()
+    };
+
+    let replay = match &mut *source {
+        InputSource::Replay(replay) if replay.started => {
+            info!("replay finished");
+            *source = InputSource::Live;
+            return;
+        }
+        InputSource::Replay(replay) => replay,
+        InputSource::Live => return,
+    };
+
+    replay.started = true;
+    saved.0 = Some(Settings {
+        seed: seed.0,
+        difficulty: difficulty.0.clone(),
+        win: *win,
+        mode: *mode,
+        arena: arena.0,
+    });
+    seed.0 = Some(replay.log.seed);
+    match ron::from_str(&replay.log.difficulty) {
+        Ok(replayed) => difficulty.0 = replayed,
+        Err(e) => warn!("replay difficulty error: {:?}", e),
+    }
+    *win = replay.log.win;
+    *mode = replay.log.mode;
+    arena.0 = from_bits(replay.log.arena);
+    input.arena = arena.0;
+}
+
+/// Puts the player's own settings back once the replay is over.
+fn restore_settings(
+    source: Res<InputSource>,
+    mut saved: ResMut<SavedSettings>,
+    mut seed: ResMut<SeedArg>,
+    mut difficulty: ResMut<ActiveDifficulty>,
+    mut win: ResMut<WinCondition>,
+    mut mode: ResMut<GameMode>,
+    mut arena: ResMut<Arena>,
+) {
+    if source.is_replay() {
+        return;
+    }
+
+    if let Some(settings) = saved.0.take() {
+        seed.0 = settings.seed;
+        difficulty.0 = settings.difficulty;
+        *win = settings.win;
+        *mode = settings.mode;
+        arena.0 = settings.arena;
+    }
+}
+
+/// The menu belongs to the player, a replay that led there is over.
+fn stop_replay(mut source: ResMut<InputSource>) {
+    if source.is_replay() {
+        info!("replay finished");
+        *source = InputSource::Live;
+    }
+}
+
+fn start_recording(
+    mut recorder: ResMut<Recorder>,
+    source: Res<InputSource>,
+    rng: Res<GameRng>,
+    difficulty: Res<ActiveDifficulty>,
+    win: Res<WinCondition>,
+    mode: Res<GameMode>,
+    arena: Res<Arena>,
+) {
+    // A run restarted from the pause menu never reached `FishWarState::GameOver`.
+    recorder.save();
+    recorder.0 = None;
+    if source.is_replay() {
+        return;
+    }
+
+    match RunLog::new(rng.seed(), &difficulty.0, *win, *mode, arena.0) {
+        Ok(log) => recorder.0 = log.new_path().map(|path| (log, path)),
+        Err(e) => warn!("record run error: {:?}", e),
+    }
+}
+
+fn save_recording(recorder: Res<Recorder>) {
+    recorder.save();
+}
+
+fn stop_recording(mut recorder: ResMut<Recorder>) {
+    recorder.save();
+    recorder.0 = None;
+}
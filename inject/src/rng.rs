@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::game_state::FishWarState;
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        let seed = SeedArg(seed_from_args());
+        app.insert_resource(GameRng::new(seed.0.unwrap_or_else(clock_seed)))
+            .insert_resource(seed)
+            .add_system_set(
+                SystemSet::on_enter(FishWarState::Game).with_system(reseed.label(Reseed)),
+            );
+    }
+}
+
+/// Runs on `on_enter(FishWarState::Game)`, systems drawing from [`GameRng`] there go after it.
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Reseed;
+
+/// All gameplay randomness, so a run can be reproduced from its seed.
+///
+/// Every stream is seeded the same but draws from its own ChaCha stream, adding randomness
+/// to one place does not shift the others.
+pub struct GameRng {
+    seed: u64,
+    /// Positions, velocities and spins of new injects.
+    pub spawn: ChaCha8Rng,
+    /// How much an inject grows when touched.
+    pub growth: ChaCha8Rng,
+    /// How much the water drops on a pop.
+    pub water: ChaCha8Rng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        let stream = |stream| {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            rng.set_stream(stream);
+            rng
+        };
+
+        Self {
+            seed,
+            spawn: stream(0),
+            growth: stream(1),
+            water: stream(2),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+/// Seed passed with `--seed <u64>`, used for every run instead of the clock.
+///
+/// A replay sets it to the seed of the recorded run.
+pub struct SeedArg(pub Option<u64>);
+
+fn seed_from_args() -> Option<u64> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--seed" {
+            return match args.next().map(|seed| seed.parse()) {
+                Some(Ok(seed)) => Some(seed),
+                Some(Err(e)) => {
+                    warn!("invalid --seed: {:?}", e);
+                    None
+                }
+                None => {
+                    warn!("--seed needs a value");
+                    None
+                }
+            };
+        }
+    }
+    None
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn clock_seed() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+// `SystemTime::now` panics on wasm32-unknown-unknown.
+#[cfg(target_arch = "wasm32")]
+fn clock_seed() -> u64 {
+    (js_sys::Date::now() * 1000.0) as u64
+}
+
+fn reseed(mut rng: ResMut<GameRng>, seed: Res<SeedArg>) {
+    *rng = GameRng::new(seed.0.unwrap_or_else(clock_seed));
+    info!("run seed: {}", rng.seed());
+}
@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+
+use crate::{
+    game_state::FishWarState,
+    inject::{CurrentInject, InjecDespawn, InjectSystem, Source, WaveNumber},
+    mode::ModeRules,
+    sim::{add_sim_system_set, SimTime},
+};
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(FishWarState::Game).with_system(setup))
+            .add_system_set(SystemSet::on_update(FishWarState::Game).with_system(sync_hud));
+        add_sim_system_set(
+            app,
+            SystemSet::on_update(FishWarState::Game)
+                .with_system(count_score.label(CountScore).after(InjectSystem::Pop)),
+        );
+    }
+}
+
+/// Applies this tick's pops to [`Score`].
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CountScore;
+
+/// Seconds a combo survives without another pop.
+const COMBO_WINDOW: f64 = 1.5;
+/// Multiplier gained by every chained pop.
+const COMBO_STEP: f32 = 0.25;
+const MAX_MULTIPLIER: f32 = 4.0;
+
+/// Score of the current (or last finished) run.
+///
+/// Reset when entering `FishWarState::Game` and kept afterwards, so the game over
+/// screen can still read it.
+#[derive(Debug, Default, Clone)]
+pub struct Score {
+    pub points: u32,
+    pub pops: u32,
+    pub combo: u32,
+    pub best_combo: u32,
+    last_pop: f64,
+}
+
+impl Score {
+    pub fn multiplier(&self) -> f32 {
+        (1.0 + self.combo.saturating_sub(1) as f32 * COMBO_STEP).min(MAX_MULTIPLIER)
+    }
+
+    fn pop(&mut self, radius: f32, now: f64) {
+        self.decay(now);
+        self.combo += 1;
+        self.best_combo = self.best_combo.max(self.combo);
+        self.pops += 1;
+        self.points += (radius * self.multiplier()).round() as u32;
+        self.last_pop = now;
+    }
+
+    fn decay(&mut self, now: f64) {
+        if self.combo > 0 && now - self.last_pop > COMBO_WINDOW {
+            self.combo = 0;
+        }
+    }
+}
+
+#[derive(Component)]
+struct Hud;
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(Score::default());
+
+    commands
+        .spawn_bundle(UiCameraBundle::default())
+        .insert(InjecDespawn);
+
+    let style = TextStyle {
+        font: asset_server.load("fonts/cabin-sketch-bold.ttf"),
+        font_size: 32.0,
+        color: Color::rgb(0.9, 0.9, 0.9),
+    };
+
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(10.0),
+                    left: Val::Px(15.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text {
+                sections: vec![
+                    TextSection {
+                        value: String::new(),
+                        style,
+                    };
+                    5
+                ],
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(Hud)
+        .insert(InjecDespawn);
+}
+
+fn count_score(mut score: ResMut<Score>, mut source: EventReader<Source>, sim_time: Res<SimTime>) {
+    let now = sim_time.seconds();
+    for Source { radius, .. } in source.iter() {
+        score.pop(*radius, now);
+    }
+    score.decay(now);
+}
+
+fn sync_hud(
+    score: Res<Score>,
+    current: Res<CurrentInject>,
+    wave: Res<WaveNumber>,
+    rules: Res<ModeRules>,
+    sim_time: Res<SimTime>,
+    mut query: Query<&mut Text, With<Hud>>,
+) {
+    let time_left = rules.time_left(sim_time.seconds());
+    for mut text in query.iter_mut() {
+        text.sections[0].value = format!("Score: {}\n", score.points);
+        text.sections[1].value = format!("Combo: {} (x{:.2})\n", score.combo, score.multiplier());
+        text.sections[2].value = format!("Wave: {}\n", wave.0);
+        text.sections[3].value = format!("Injects left: {}", current.0);
+        text.sections[4].value = match time_left {
+            Some(seconds) => format!("\n{}: {:.0}s left", rules.mode.name(), seconds.ceil()),
+            None => format!("\n{}", rules.mode.name()),
+        };
+    }
+}
@@ -0,0 +1,13 @@
+//! 着色器的资源路径，由 `shaders-builder` 按 `shaders-builder/shaders.ron` 生成，不要手动修改。
+//!
+//! 原生平台加载 SPIR-V，网页版（WebGL2）加载 `cargo gen-shader` 翻译出的 WGSL。
+
+#[cfg(not(target_arch = "wasm32"))]
+pub const FRAGMENT: &str = "shaders/fragment.spv";
+#[cfg(target_arch = "wasm32")]
+pub const FRAGMENT: &str = "shaders/fragment.wgsl";
+
+#[cfg(not(target_arch = "wasm32"))]
+pub const VERTEX: &str = "shaders/vertex.spv";
+#[cfg(target_arch = "wasm32")]
+pub const VERTEX: &str = "shaders/vertex.wgsl";
@@ -0,0 +1,147 @@
+use std::time::Duration;
+
+use bevy::{ecs::schedule::ShouldRun, prelude::*};
+use bevy_tweening::{TweenCompleted, Tweenable};
+use heron::{PhysicsPlugin, PhysicsSteps};
+
+use crate::{game_state::FishWarState, replay::FrameInput};
+
+/// Simulated time covered by one tick of `FishWarState::Game`.
+///
+/// Frames run as many whole ticks as the wall clock allows and record how many they ran, so a
+/// recorded run plays back exactly the same way at any frame rate.
+pub const SIM_STEP: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// Most ticks a single frame runs, the rest of a longer hitch is dropped.
+pub const MAX_TICKS: u8 = 8;
+
+/// Adds heron's [`PhysicsPlugin`] and runs its schedule once per tick of [`FrameInput::ticks`].
+pub struct SimPlugin;
+
+impl Plugin for SimPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(PhysicsPlugin::default())
+            .insert_resource(PhysicsSteps::every_frame(SIM_STEP))
+            .init_resource::<FrameInput>()
+            .init_resource::<SimTime>()
+            .add_system_set(SystemSet::on_enter(FishWarState::Game).with_system(reset_sim_time))
+            .stage(heron::stage::ROOT, |schedule: &mut Schedule| {
+                schedule.set_run_criteria(run_sim_ticks.into_system())
+            });
+    }
+}
+
+/// Adds gameplay systems that run once per tick, right before every physics step.
+///
+/// [`SimPlugin`] has to be added first.
+pub fn add_sim_system_set(app: &mut App, system_set: SystemSet) -> &mut App {
+    app.stage(heron::stage::ROOT, |schedule: &mut Schedule| {
+        schedule.add_system_set_to_stage(heron::stage::UPDATE, system_set)
+    })
+}
+
+/// Time since the start of the current run, advanced by [`SIM_STEP`] every tick.
+#[derive(Debug, Default)]
+pub struct SimTime {
+    ticks: u64,
+    /// Set once the run ended, the remaining ticks of the frame are skipped.
+    stopped: bool,
+}
+
+impl SimTime {
+    pub fn seconds(&self) -> f64 {
+        self.ticks as f64 * SIM_STEP.as_secs_f64()
+    }
+
+    /// Ends the run's simulation, e.g. when it queued `FishWarState::GameOver`. The state only
+    /// changes with the next frame, after the remaining ticks of this one.
+    pub fn stop(&mut self) {
+        self.stopped = true;
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+}
+
+/// Turns wall clock time into whole ticks, carrying the rest over to the next frame.
+#[derive(Debug, Default)]
+pub struct SimClock {
+    carry: Duration,
+}
+
+impl SimClock {
+    /// Ticks due after another `delta` passed, at most [`MAX_TICKS`].
+    pub fn advance(&mut self, delta: Duration) -> u8 {
+        self.carry += delta;
+        let due = self.carry.as_nanos() / SIM_STEP.as_nanos();
+        let ticks = due.min(MAX_TICKS.into()) as u8;
+        self.carry -= SIM_STEP * u32::from(ticks);
+        if self.carry >= SIM_STEP {
+            self.carry = Duration::ZERO;
+        }
+        ticks
+    }
+
+    pub fn reset(&mut self) {
+        self.carry = Duration::ZERO;
+    }
+}
+
+/// Like `bevy_tweening::Animator`, but ticked with [`SIM_STEP`] so it follows [`SimTime`].
+///
+/// Its system has to run once per tick, see [`add_sim_system_set`].
+#[derive(Component)]
+pub struct SimAnimator<T: Component> {
+    tweenable: Box<dyn Tweenable<T> + Send + Sync + 'static>,
+}
+
+impl<T: Component> SimAnimator<T> {
+    pub fn new(tween: impl Tweenable<T> + Send + Sync + 'static) -> Self {
+        Self {
+            tweenable: Box::new(tween),
+        }
+    }
+
+    pub fn progress(&self) -> f32 {
+        self.tweenable.progress()
+    }
+}
+
+pub fn sim_animator_system<T: Component>(
+    mut query: Query<(Entity, &mut T, &mut SimAnimator<T>)>,
+    mut event_writer: EventWriter<TweenCompleted>,
+) {
+    for (entity, ref mut target, ref mut animator) in query.iter_mut() {
+        animator
+            .tweenable
+            .tick(SIM_STEP, target, entity, &mut event_writer);
+    }
+}
+
+fn reset_sim_time(mut sim_time: ResMut<SimTime>) {
+    *sim_time = SimTime::default();
+}
+
+/// Run criteria of heron's schedule, counts the ticks that ran.
+fn run_sim_ticks(
+    input: Res<FrameInput>,
+    state: Res<State<FishWarState>>,
+    mut sim_time: ResMut<SimTime>,
+    mut left: Local<Option<u8>>,
+) -> ShouldRun {
+    let ticks = match *left {
+        Some(ticks) => {
+            sim_time.ticks += 1;
+            ticks
+        }
+        None => input.ticks,
+    };
+
+    if ticks == 0 || sim_time.stopped || state.current() != &FishWarState::Game {
+        *left = None;
+        return ShouldRun::No;
+    }
+    *left = Some(ticks - 1);
+    ShouldRun::YesAndCheckAgain
+}
@@ -1,7 +1,8 @@
-use std::time::Duration;
+use std::{marker::PhantomData, time::Duration};
 
 use bevy::{
     core::Time,
+    ecs::system::Resource,
     math::{Rect, Size, Vec2, Vec3, Vec4},
     prelude::*,
     sprite::{MaterialMesh2dBundle, Mesh2dHandle},
@@ -16,8 +17,12 @@ use bevy_tweening::{
 
 use crate::utils::despawn_screen;
 use crate::{
+    difficulty::DifficultyPreset,
     game_state::FishWarState,
-    waves::{WavesMaterial, WavesPropertiesLens},
+    highscore::HighScores,
+    mode::GameMode,
+    victory::WinCondition,
+    waves::{waves_tween, WavesMaterial, WavesMesh, WavesTime, WavesViewport},
 };
 pub struct StartPagePlugin;
 
@@ -27,6 +32,10 @@ impl Plugin for StartPagePlugin {
             .add_system_set(
                 SystemSet::on_update(FishWarState::Menu)
                     .with_system(button_system)
+                    .with_system(cycle_button_system::<DifficultyPreset>)
+                    .with_system(cycle_button_system::<WinCondition>)
+                    .with_system(cycle_button_system::<GameMode>)
+                    .with_system(sync_high_score_table)
                     .with_system(component_animator_system::<UiColor>)
                     .with_system(sync_with_time)
                     .with_system(sync_with_window_size),
@@ -39,9 +48,9 @@ impl Plugin for StartPagePlugin {
     }
 }
 
-const NORMAL_BUTTON: Color = Color::SEA_GREEN;
-const HOVERED_BUTTON: Color = Color::DARK_GREEN;
-const PRESSED_BUTTON: Color = Color::MIDNIGHT_BLUE;
+pub const NORMAL_BUTTON: Color = Color::SEA_GREEN;
+pub const HOVERED_BUTTON: Color = Color::DARK_GREEN;
+pub const PRESSED_BUTTON: Color = Color::MIDNIGHT_BLUE;
 
 fn button_system(
     mut commands: Commands,
@@ -52,7 +61,7 @@ fn button_system(
             Option<&mut Animator<Transform>>,
             Option<&mut Animator<UiColor>>,
         ),
-        (Changed<Interaction>, With<Button>),
+        (Changed<Interaction>, With<StartButton>),
     >,
     mut offset: ResMut<Offset>,
 ) {
@@ -208,9 +217,101 @@ impl Lens<UiColor> for UiColorColorLens {
         target.0 = value.into();
     }
 }
+/// A setting the start page cycles through with a button.
+pub trait Cycle {
+    fn next(&self) -> Self;
+    /// Text of the button, e.g. "Mode: Classic".
+    fn label(&self) -> String;
+}
+
+impl Cycle for DifficultyPreset {
+    fn next(&self) -> Self {
+        DifficultyPreset::next(*self)
+    }
+
+    fn label(&self) -> String {
+        format!("Difficulty: {}", self.name())
+    }
+}
+
+impl Cycle for WinCondition {
+    fn next(&self) -> Self {
+        WinCondition::next(*self)
+    }
+
+    fn label(&self) -> String {
+        format!("Goal: {}", self.describe())
+    }
+}
+
+impl Cycle for GameMode {
+    fn next(&self) -> Self {
+        GameMode::next(*self)
+    }
+
+    fn label(&self) -> String {
+        format!("Mode: {}", self.name())
+    }
+}
+
+/// Moves `R` on when its button is clicked, and keeps the label of the button up to date.
+fn cycle_button_system<R: Resource + Cycle>(
+    mut setting: ResMut<R>,
+    mut interaction_query: Query<
+        (&Interaction, &mut UiColor),
+        (Changed<Interaction>, With<CycleButton<R>>),
+    >,
+    label_query: Query<&Children, With<CycleButton<R>>>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (interaction, mut color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => {
+                *setting = setting.next();
+                color.0 = PRESSED_BUTTON;
+            }
+            Interaction::Hovered => color.0 = HOVERED_BUTTON,
+            Interaction::None => color.0 = NORMAL_BUTTON,
+        }
+    }
+
+    if setting.is_changed() {
+        for children in label_query.iter() {
+            for child in children.iter() {
+                if let Ok(mut text) = text_query.get_mut(*child) {
+                    text.sections[0].value = setting.label();
+                }
+            }
+        }
+    }
+}
+
+fn sync_high_score_table(
+    mode: Res<GameMode>,
+    high_scores: Res<HighScores>,
+    mut query: Query<&mut Text, With<HighScoreTable>>,
+) {
+    if mode.is_changed() {
+        for mut text in query.iter_mut() {
+            text.sections[0].value = high_scores.table(*mode);
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct StartMenu;
 
+#[derive(Component)]
+struct StartButton;
+
+/// Shows the high scores of the selected [`GameMode`].
+#[derive(Component)]
+struct HighScoreTable;
+
+/// Cycles through the values of `R`, its text child shows the current one.
+#[derive(Component)]
+struct CycleButton<R>(PhantomData<R>);
+
 #[derive(Component)]
 pub struct Wave;
 
@@ -220,6 +321,12 @@ fn setup(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<WavesMaterial>>,
     windows: Res<Windows>,
+    mut viewport: ResMut<WavesViewport>,
+    waves_mesh: Res<WavesMesh>,
+    high_scores: Res<HighScores>,
+    preset: Res<DifficultyPreset>,
+    condition: Res<WinCondition>,
+    mode: Res<GameMode>,
 ) {
     commands.insert_resource(Offset::default());
 
@@ -240,6 +347,7 @@ fn setup(
             ..Default::default()
         })
         .insert(StartMenu)
+        .insert(StartButton)
         .with_children(|parent| {
             parent.spawn_bundle(TextBundle {
                 text: Text::with_section(
@@ -254,39 +362,46 @@ fn setup(
                 ..Default::default()
             });
         });
-    let lens = WavesPropertiesLens {
-        start: WavesMaterial {
-            amplitude: 0.2,
-            angular_velocity: 0.8,
-            frequency: 3.,
-            color: Color::SEA_GREEN.into(),
-            time: Default::default(),
-            offset: Default::default(),
-        },
-        end: WavesMaterial {
-            amplitude: 0.15,
-            angular_velocity: 0.8,
-            frequency: 3.5,
-            color: Color::PINK.into(),
-            time: Default::default(),
-            offset: Default::default(),
-        },
-    };
-    let tween = Tween::new(
-        EaseMethod::Linear,
-        TweeningType::PingPong,
-        Duration::from_secs_f32(15.0),
-        lens,
-    );
+
+    let font = asset_server.load("fonts/rock-salt-regular.ttf");
+    spawn_cycle_button(&mut commands, font.clone(), &*preset, 15.0);
+    spawn_cycle_button(&mut commands, font.clone(), &*condition, 75.0);
+    spawn_cycle_button(&mut commands, font, &*mode, 135.0);
+
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(15.0),
+                    right: Val::Px(15.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                high_scores.table(*mode),
+                TextStyle {
+                    font: asset_server.load("fonts/rock-salt-regular.ttf"),
+                    font_size: 20.0,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        })
+        .insert(StartMenu)
+        .insert(HighScoreTable);
 
     let waves = materials.add(WavesMaterial::default());
     let window = windows.get_primary().unwrap();
+    *viewport = WavesViewport::of(window);
 
     commands
         .spawn()
         .insert_bundle(MaterialMesh2dBundle {
             mesh: meshes
-                .add(new_waves_mesh(window.width(), window.height()))
+                .add(new_waves_mesh(window.width(), window.height(), &waves_mesh))
                 .into(),
             transform: Transform {
                 translation: Vec3::new(0., 0., 0.),
@@ -296,7 +411,7 @@ fn setup(
             material: waves.clone(),
             ..Default::default()
         })
-        .insert(AssetAnimator::new(waves, tween))
+        .insert(AssetAnimator::new(waves, waves_tween()))
         .insert(StartMenu)
         .insert(Wave);
 
@@ -306,25 +421,73 @@ fn setup(
         .insert(StartMenu);
 }
 
+/// A button in the lower left corner, `bottom` pixels above the edge of the window.
+fn spawn_cycle_button<R: Resource + Cycle>(
+    commands: &mut Commands,
+    font: Handle<Font>,
+    setting: &R,
+    bottom: f32,
+) {
+    commands
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(260.0), Val::Px(50.0)),
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    bottom: Val::Px(bottom),
+                    left: Val::Px(15.0),
+                    ..Default::default()
+                },
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: NORMAL_BUTTON.into(),
+            ..Default::default()
+        })
+        .insert(StartMenu)
+        .insert(CycleButton::<R>(PhantomData))
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    setting.label(),
+                    TextStyle {
+                        font,
+                        font_size: 20.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+        });
+}
+
 pub fn sync_with_window_size(
     mut window_size: EventReader<WindowResized>,
     handle_query: Query<&Mesh2dHandle, With<Wave>>,
     mut meshes: ResMut<Assets<Mesh>>,
+    windows: Res<Windows>,
+    mut viewport: ResMut<WavesViewport>,
+    waves_mesh: Res<WavesMesh>,
 ) {
     for window_resized in window_size.iter() {
+        if let Some(window) = windows.get(window_resized.id) {
+            *viewport = WavesViewport::of(window);
+        }
+
         if let Some(mesh) = handle_query
             .get_single()
             .ok()
             .and_then(|handle| meshes.get_mut(&handle.0))
         {
-            *mesh = new_waves_mesh(window_resized.width, window_resized.height);
+            *mesh = new_waves_mesh(window_resized.width, window_resized.height, &waves_mesh);
         };
     }
 }
 
-pub fn new_waves_mesh(width: f32, height: f32) -> Mesh {
-    let size = Vec2::new(width, height);
-    Mesh::from(shape::Quad { size, flip: false })
+pub fn new_waves_mesh(width: f32, height: f32, waves_mesh: &WavesMesh) -> Mesh {
+    waves_mesh.build(Vec2::new(width, height))
 }
 
 fn sync_with_time(
@@ -333,10 +496,11 @@ fn sync_with_time(
     time: Res<Time>,
     offset: Res<Offset>,
     mut game_state: ResMut<State<FishWarState>>,
+    mut waves_time: ResMut<WavesTime>,
 ) {
+    waves_time.0 += time.delta_seconds();
     for handle in query_waves.iter() {
         if let Some(waves) = materials.get_mut(handle) {
-            waves.time = time.seconds_since_startup() as f32;
             if waves.offset <= 0.0 {
                 if let Err(e) = game_state.set(FishWarState::Game) {
                     warn!("set state error: {:?}", e);
@@ -0,0 +1,215 @@
+use bevy::prelude::*;
+use codec::{Decode, Encode};
+
+use crate::{
+    difficulty::ActiveDifficulty,
+    game_over::{quit_game, reopen_game},
+    game_state::FishWarState,
+    highscore::RecordRun,
+    inject::{InjectSystem, WaveNumber},
+    rng::Reseed,
+    score::{CountScore, Score},
+    sim::{add_sim_system_set, SimTime},
+    utils::despawn_screen,
+};
+
+pub struct VictoryPlugin;
+
+impl Plugin for VictoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WinCondition>()
+            .add_system_set(
+                SystemSet::on_enter(FishWarState::Game).with_system(start_run.after(Reseed)),
+            )
+            .add_system_set(
+                SystemSet::on_enter(FishWarState::Victory).with_system(setup.after(RecordRun)),
+            )
+            .add_system_set(
+                SystemSet::on_update(FishWarState::Victory)
+                    .with_system(quit_game)
+                    .with_system(reopen_game),
+            )
+            .add_system_set(
+                SystemSet::on_exit(FishWarState::Victory)
+                    .with_system(despawn_screen::<VictoryDespawn>),
+            );
+        add_sim_system_set(
+            app,
+            SystemSet::on_update(FishWarState::Game).with_system(
+                check_victory
+                    .after(InjectSystem::Drain)
+                    .after(InjectSystem::NextWave)
+                    .after(CountScore),
+            ),
+        );
+    }
+}
+
+/// How the next run is won, picked on the start page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub enum WinCondition {
+    /// Play until the water runs out.
+    Never,
+    /// Clear this many waves. `WaveNumber` stops at 255, so at most 254 can be cleared.
+    SurviveWaves(u8),
+    ReachScore(u32),
+    /// Clear the last wave listed in the `ActiveDifficulty`.
+    ClearFinalWave,
+}
+
+impl Default for WinCondition {
+    fn default() -> Self {
+        Self::Never
+    }
+}
+
+impl WinCondition {
+    const PRESETS: [Self; 4] = [
+        Self::Never,
+        Self::SurviveWaves(10),
+        Self::ReachScore(2000),
+        Self::ClearFinalWave,
+    ];
+
+    pub fn next(self) -> Self {
+        let index = Self::PRESETS
+            .iter()
+            .position(|condition| *condition == self)
+            .map_or(0, |index| index + 1);
+        Self::PRESETS[index % Self::PRESETS.len()]
+    }
+
+    pub fn describe(self) -> String {
+        match self {
+            Self::Never => "No goal".to_string(),
+            Self::SurviveWaves(waves) => format!("Survive {} waves", waves),
+            Self::ReachScore(points) => format!("Reach {} points", points),
+            Self::ClearFinalWave => "Clear the final wave".to_string(),
+        }
+    }
+}
+
+/// The [`WinCondition`] of the current (or last finished) run, fixed when it starts.
+#[derive(Debug)]
+pub struct RunGoal {
+    pub condition: WinCondition,
+    /// Waves to clear, resolved from the difficulty for `WinCondition::ClearFinalWave`.
+    waves: u8,
+    /// Waves cleared so far, kept after the run for the victory screen.
+    cleared: u8,
+}
+
+#[derive(Component)]
+struct VictoryDespawn;
+
+fn start_run(
+    mut commands: Commands,
+    condition: Res<WinCondition>,
+    difficulty: Res<ActiveDifficulty>,
+) {
+    let waves = match *condition {
+        WinCondition::SurviveWaves(waves) => waves,
+        WinCondition::ClearFinalWave => difficulty.0.waves.len().min(254) as u8,
+        WinCondition::Never | WinCondition::ReachScore(_) => 0,
+    };
+
+    commands.insert_resource(RunGoal {
+        condition: *condition,
+        waves,
+        cleared: 0,
+    });
+}
+
+/// Runs after the water was drained, a run that ran dry in the same tick is lost.
+fn check_victory(
+    mut goal: ResMut<RunGoal>,
+    wave: Res<WaveNumber>,
+    score: Res<Score>,
+    mut sim_time: ResMut<SimTime>,
+    mut game_state: ResMut<State<FishWarState>>,
+) {
+    // `drain_water` already queued `FishWarState::GameOver`.
+    if sim_time.is_stopped() {
+        return;
+    }
+    goal.cleared = wave.0.saturating_sub(1);
+
+    let won = match goal.condition {
+        WinCondition::Never => false,
+        WinCondition::SurviveWaves(_) | WinCondition::ClearFinalWave => goal.cleared >= goal.waves,
+        WinCondition::ReachScore(points) => score.points >= points,
+    };
+
+    if won {
+        sim_time.stop();
+        if let Err(e) = game_state.set(FishWarState::Victory) {
+            warn!("set state error: {:?}", e);
+        };
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    goal: Res<RunGoal>,
+    score: Res<Score>,
+    sim_time: Res<SimTime>,
+) {
+    commands
+        .spawn_bundle(UiCameraBundle::default())
+        .insert(VictoryDespawn);
+
+    let seconds = sim_time.seconds() as u32;
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::rgba(0.0, 0.0, 0.0, 0.0).into(),
+            ..Default::default()
+        })
+        .insert(VictoryDespawn)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    "Victory!",
+                    TextStyle {
+                        font: asset_server.load("fonts/finger-paint-regular.ttf"),
+                        font_size: 100.0,
+                        color: Color::GOLD,
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    format!(
+                        "{}\nScore: {}   Pops: {}   Best combo: {}\nWaves cleared: {}   Time: {:02}:{:02}\n\n'G' to play again, 'M' for the menu, 'Q' to quit",
+                        goal.condition.describe(),
+                        score.points,
+                        score.pops,
+                        score.best_combo,
+                        goal.cleared,
+                        seconds / 60,
+                        seconds % 60,
+                    ),
+                    TextStyle {
+                        font: asset_server.load("fonts/finger-paint-regular.ttf"),
+                        font_size: 36.0,
+                        color: Color::SEA_GREEN,
+                    },
+                    TextAlignment {
+                        horizontal: HorizontalAlign::Center,
+                        ..Default::default()
+                    },
+                ),
+                ..Default::default()
+            });
+        });
+}
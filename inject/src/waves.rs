@@ -1,18 +1,50 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
 use bevy::{
     ecs::system::{lifetimeless::SRes, SystemParamItem},
     math::Vec4,
     prelude::*,
     reflect::{Reflect, TypeUuid},
-    render::render_resource::std140::{AsStd140, Std140},
-    render::{render_asset::RenderAsset, render_resource::*, renderer::RenderDevice},
+    render::{
+        render_asset::RenderAsset,
+        render_resource::*,
+        renderer::{RenderDevice, RenderQueue},
+        RenderApp, RenderStage,
+    },
     sprite::{Material2d, Material2dPipeline, Material2dPlugin},
 };
-use bevy_tweening::{asset_animator_system, Lens, Lerp};
+use bevy_tweening::{asset_animator_system, EaseMethod, Lens, Lerp, Tracks, Tween, TweeningType};
+use shaders::waves::{Globals, Layer, Properties, Ripple, LAYERS, RIPPLES};
+
+use crate::shader_paths;
+
+// 编译期确认两种格式都在，缺了翻译结果就运行 `cargo gen-shader`。
+// 原生构建也检查，这样只跑原生的 CI 也能发现网页版缺文件。
+const _: &[u8] = include_bytes!("../assets/shaders/vertex.spv");
+const _: &str = include_str!("../assets/shaders/vertex.wgsl");
+const _: &[u8] = include_bytes!("../assets/shaders/fragment.spv");
+const _: &str = include_str!("../assets/shaders/fragment.wgsl");
 
-#[derive(Debug, Copy, Clone, TypeUuid, Component, Reflect, AsStd140, PartialEq)]
+#[derive(Debug, Copy, Clone, TypeUuid, Component, Reflect, PartialEq)]
 #[uuid = "817a079c-3acf-484a-b4b3-a6254c114200"]
 #[cfg_attr(feature = "dev", derive(bevy_inspector_egui::Inspectable))]
 pub struct WavesMaterial {
+    /// 偏距（设为 0.5 使得波浪垂直居中于屏幕），所有层一起移动
+    pub offset: f32,
+    /// 最后面的一层
+    pub back: WaveLayer,
+    pub middle: WaveLayer,
+    /// 最前面的一层
+    pub front: WaveLayer,
+}
+
+/// 一层波浪，几层的速度和高度错开就有了纵深感。
+#[derive(Debug, Copy, Clone, Reflect, PartialEq)]
+#[cfg_attr(feature = "dev", derive(bevy_inspector_egui::Inspectable))]
+pub struct WaveLayer {
     /// 振幅（控制波浪顶端和底端的高度）
     ///
     /// 曲线最高点与最低点的差值，表现为曲线的整体高度
@@ -23,32 +55,286 @@ pub struct WavesMaterial {
     pub angular_velocity: f32,
     /// 频率（控制波浪移动的速度）
     pub frequency: f32,
-    /// 偏距（设为 0.5 使得波浪垂直居中于屏幕）
+    /// 相对于材质 `offset` 的偏距，负值让这一层更高
     pub offset: f32,
-    // 底色
+    /// 底色，`w` 是这一层的不透明度
     pub color: Vec4,
-    // 时间
-    pub time: f32,
+    /// 陡度（0 是圆滑的正弦，接近 1 时波峰变尖）
+    pub steepness: f32,
+    /// 移动方向（1 向左，-1 向右）
+    pub direction: f32,
+    /// 波峰下泡沫带的宽度，0 表示没有泡沫
+    pub foam_width: f32,
+    /// 水面边缘的过渡宽度，用来抗锯齿
+    pub edge_softness: f32,
+    /// 泡沫颜色，`w` 是泡沫的浓度
+    pub foam_color: Vec4,
+    /// 最底部的颜色，从水面往下由 `color` 渐变过去
+    pub deep_color: Vec4,
 }
 
-impl Default for WavesMaterial {
+impl Default for WaveLayer {
     fn default() -> Self {
         Self {
             amplitude: 3.0,
             angular_velocity: 0.3,
             frequency: 5.,
-            offset: 1.0,
+            offset: 0.0,
             color: Color::GREEN.into(),
-            time: Default::default(),
+            steepness: 0.5,
+            direction: 1.0,
+            foam_width: 0.02,
+            edge_softness: 0.003,
+            foam_color: Color::rgba(1.0, 1.0, 1.0, 0.8).into(),
+            deep_color: Color::rgb(0.0, 0.1, 0.2).into(),
+        }
+    }
+}
+
+impl Default for WavesMaterial {
+    fn default() -> Self {
+        let front = WaveLayer::default();
+
+        Self {
+            offset: 1.0,
+            back: front.behind(2),
+            middle: front.behind(1),
+            front,
+        }
+    }
+}
+
+impl WavesMaterial {
+    /// 从后往前排列的各层。
+    pub fn layers(&self) -> [&WaveLayer; LAYERS] {
+        [&self.back, &self.middle, &self.front]
+    }
+
+    pub fn layers_mut(&mut self) -> [&mut WaveLayer; LAYERS] {
+        [&mut self.back, &mut self.middle, &mut self.front]
+    }
+
+    /// 上传给着色器的 uniform，布局由 `shaders::waves::Properties` 决定。
+    pub fn properties(&self) -> Properties {
+        Properties {
+            layers: self.layers().map(|layer| Layer {
+                amplitude: layer.amplitude,
+                angular_velocity: layer.angular_velocity,
+                frequency: layer.frequency,
+                offset: self.offset + layer.offset,
+                color: shaders::glam::Vec4::from(layer.color.to_array()),
+                steepness: layer.steepness,
+                direction: layer.direction,
+                foam_width: layer.foam_width,
+                edge_softness: layer.edge_softness,
+                foam_color: shaders::glam::Vec4::from(layer.foam_color.to_array()),
+                deep_color: shaders::glam::Vec4::from(layer.deep_color.to_array()),
+            }),
         }
     }
 }
 
-#[derive(Clone, Component)]
+impl WaveLayer {
+    /// `depth` 层之后的一层：更高、更慢、更透明。
+    pub fn behind(&self, depth: u8) -> Self {
+        let depth = f32::from(depth);
+        let mut color = self.color;
+        color.w *= 0.6_f32.powf(depth);
+
+        Self {
+            frequency: self.frequency * 0.7_f32.powf(depth),
+            offset: self.offset - 0.04 * depth,
+            color,
+            ..*self
+        }
+    }
+
+    fn lerp(&self, end: &Self, ratio: f32) -> Self {
+        Self {
+            amplitude: self.amplitude.lerp(&end.amplitude, &ratio),
+            angular_velocity: self.angular_velocity.lerp(&end.angular_velocity, &ratio),
+            frequency: self.frequency.lerp(&end.frequency, &ratio),
+            offset: self.offset.lerp(&end.offset, &ratio),
+            color: self.color.lerp(end.color, ratio),
+            steepness: self.steepness.lerp(&end.steepness, &ratio),
+            direction: self.direction.lerp(&end.direction, &ratio),
+            foam_width: self.foam_width.lerp(&end.foam_width, &ratio),
+            edge_softness: self.edge_softness.lerp(&end.edge_softness, &ratio),
+            foam_color: self.foam_color.lerp(end.foam_color, ratio),
+            deep_color: self.deep_color.lerp(end.deep_color, ratio),
+        }
+    }
+}
+
+/// 波浪的时间，所有波浪共用，每帧写进同一个全局 uniform，不会让材质重新准备。
+///
+/// 只会往前走：菜单里按真实时间，游戏里每个模拟 tick 走一步，暂停时停住，切换状态时波浪不会跳。
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WavesTime(pub f32);
+
+/// 窗口的物理像素大小，和时间一起写进全局 uniform，窗口大小变化时由 `sync_with_window_size` 更新。
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WavesViewport(pub Vec2);
+
+impl WavesViewport {
+    pub fn of(window: &Window) -> Self {
+        Self(Vec2::new(
+            window.physical_width() as f32,
+            window.physical_height() as f32,
+        ))
+    }
+}
+
+/// 水面上的涟漪，和时间一起写进全局 uniform，其他系统也可以往里加。
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Ripples {
+    ripples: [Ripple; RIPPLES],
+    next: usize,
+}
+
+impl Ripples {
+    /// 在波浪四边形的 `uv` 处（原点在左上角）加一个涟漪，`start` 用 [`WavesTime`] 的时钟。
+    ///
+    /// 满了就覆盖最旧的一个。
+    pub fn push(&mut self, uv: Vec2, start: f32, strength: f32) {
+        self.ripples[self.next] = Ripple {
+            position: shaders::glam::Vec2::new(uv.x, uv.y),
+            start,
+            strength,
+        };
+        self.next = (self.next + 1) % RIPPLES;
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// 波浪网格横向分成几列，1 列就是一个四边形。
+///
+/// 多于 1 列时顶点着色器把网格的顶边移到水面上，水面以上的像素不用再画，窗口大的时候
+/// 能省下不少填充率。网格只在生成时读取它，要在波浪生成之前设置好。
+///
+/// 默认 [`WavesMesh::DEFAULT_COLUMNS`] 列，可以用 `--waves-columns <N>` 修改。
+#[derive(Debug, Clone, Copy)]
+pub struct WavesMesh {
+    pub columns: u32,
+}
+
+impl Default for WavesMesh {
+    fn default() -> Self {
+        Self {
+            columns: Self::DEFAULT_COLUMNS,
+        }
+    }
+}
+
+impl WavesMesh {
+    pub const DEFAULT_COLUMNS: u32 = 32;
+
+    /// 命令行里有 `--waves-columns <N>` 时用 `N` 列，没有或者不合法时用默认值。
+    pub fn from_args() -> Self {
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            if arg == "--waves-columns" {
+                return match args.next().map(|columns| columns.parse()) {
+                    Some(Ok(columns)) if columns > 0 => Self { columns },
+                    Some(Ok(_)) => {
+                        warn!("--waves-columns must be at least 1");
+                        Self::default()
+                    }
+                    Some(Err(e)) => {
+                        warn!("invalid --waves-columns: {:?}", e);
+                        Self::default()
+                    }
+                    None => {
+                        warn!("--waves-columns needs a value");
+                        Self::default()
+                    }
+                };
+            }
+        }
+        Self::default()
+    }
+
+    /// 以原点为中心、`size` 大小的网格，uv 的原点在左上角。
+    pub fn build(&self, size: Vec2) -> Mesh {
+        let columns = self.columns.max(1);
+        let half = size * 0.5;
+
+        let mut positions = Vec::new();
+        let mut uvs = Vec::new();
+        for column in 0..=columns {
+            let u = column as f32 / columns as f32;
+            let x = -half.x + size.x * u;
+            // 每列一个顶边上的顶点和一个底边上的顶点
+            positions.push([x, half.y, 0.0]);
+            positions.push([x, -half.y, 0.0]);
+            uvs.push([u, 0.0]);
+            uvs.push([u, 1.0]);
+        }
+        let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+
+        let mut indices = Vec::new();
+        for column in 0..columns {
+            let top_left = column * 2;
+            let (bottom_left, top_right, bottom_right) = (top_left + 1, top_left + 2, top_left + 3);
+            indices.extend([top_left, bottom_left, bottom_right]);
+            indices.extend([top_left, bottom_right, top_right]);
+        }
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        mesh.set_indices(Some(Indices::U32(indices)));
+        mesh
+    }
+}
+
+/// 以 `size` 大小的波浪四边形（中心在原点）上的世界坐标算出 uv。
+pub fn world_to_uv(position: Vec2, size: Vec2) -> Vec2 {
+    Vec2::new(position.x / size.x + 0.5, 0.5 - position.y / size.y)
+}
+
+/// 渲染世界里的全局 uniform buffer，内容是 [`Globals`]。
+pub struct WavesGlobals {
+    buffer: Buffer,
+}
+
+impl FromWorld for WavesGlobals {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.get_resource::<RenderDevice>().unwrap();
+        let buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("waves_globals_buffer"),
+            size: Globals::SIZE as u64,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self { buffer }
+    }
+}
+
+/// 被替换掉的 [`GpuWavesMaterial`] 留下的 buffer 和 bind group。
+///
+/// 材质一变 `prepare_asset` 就会重新执行，这里的 buffer 可以直接 `write_buffer` 复用，
+/// 不用每次都创建新的 buffer 和 bind group。
+#[derive(Default, Clone)]
+pub struct WavesBindings(Arc<Mutex<Vec<(Buffer, BindGroup)>>>);
+
+#[derive(Component)]
 pub struct GpuWavesMaterial {
-    // properties_buffer: Buffer,
-    // time_buffer: Buffer,
+    properties_buffer: Buffer,
     bind_group: BindGroup,
+    recycle: WavesBindings,
+}
+
+impl Drop for GpuWavesMaterial {
+    fn drop(&mut self) {
+        if let Ok(mut bindings) = self.recycle.0.lock() {
+            bindings.push((self.properties_buffer.clone(), self.bind_group.clone()));
+        }
+    }
 }
 
 pub struct WavesPlugin;
@@ -56,7 +342,18 @@ pub struct WavesPlugin;
 impl Plugin for WavesPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(Material2dPlugin::<WavesMaterial>::default())
+            .init_resource::<WavesTime>()
+            .init_resource::<WavesViewport>()
+            .init_resource::<Ripples>()
+            .insert_resource(WavesMesh::from_args())
             .add_system(asset_animator_system::<WavesMaterial>);
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .init_resource::<WavesGlobals>()
+                .init_resource::<WavesBindings>()
+                .add_system_to_stage(RenderStage::Extract, extract_waves_globals)
+                .add_system_to_stage(RenderStage::Prepare, prepare_waves_globals);
+        }
         #[cfg(feature = "dev")]
         {
             let mut registry = app
@@ -73,7 +370,13 @@ impl RenderAsset for WavesMaterial {
 
     type PreparedAsset = GpuWavesMaterial;
 
-    type Param = (SRes<RenderDevice>, SRes<Material2dPipeline<Self>>);
+    type Param = (
+        SRes<RenderDevice>,
+        SRes<RenderQueue>,
+        SRes<Material2dPipeline<Self>>,
+        SRes<WavesGlobals>,
+        SRes<WavesBindings>,
+    );
 
     #[allow(clippy::clone_on_copy)]
     fn extract_asset(&self) -> Self::ExtractedAsset {
@@ -82,36 +385,66 @@ impl RenderAsset for WavesMaterial {
 
     fn prepare_asset(
         extracted_asset: Self::ExtractedAsset,
-        (render_device, pipeline): &mut SystemParamItem<Self::Param>,
+        (render_device, render_queue, pipeline, globals, bindings): &mut SystemParamItem<
+            Self::Param,
+        >,
     ) -> Result<
         Self::PreparedAsset,
         bevy::render::render_asset::PrepareAssetError<Self::ExtractedAsset>,
     > {
-        let properties_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
-            contents: extracted_asset.as_std140().as_bytes(),
-            label: Some("properties_buffer"),
-            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
-        });
+        let properties = extracted_asset.properties();
+        let recycled = bindings
+            .0
+            .lock()
+            .ok()
+            .and_then(|mut bindings| bindings.pop());
+
+        let (properties_buffer, bind_group) = match recycled {
+            Some((properties_buffer, bind_group)) => {
+                render_queue.write_buffer(&properties_buffer, 0, bytemuck::bytes_of(&properties));
+                (properties_buffer, bind_group)
+            }
+            None => {
+                let properties_buffer =
+                    render_device.create_buffer_with_data(&BufferInitDescriptor {
+                        contents: bytemuck::bytes_of(&properties),
+                        label: Some("properties_buffer"),
+                        usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                    });
+
+                let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
+                    entries: &[
+                        BindGroupEntry {
+                            binding: 0,
+                            resource: properties_buffer.as_entire_binding(),
+                        },
+                        BindGroupEntry {
+                            binding: 1,
+                            resource: globals.buffer.as_entire_binding(),
+                        },
+                    ],
+                    label: None,
+                    layout: &pipeline.material2d_layout,
+                });
+                (properties_buffer, bind_group)
+            }
+        };
 
-        let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
-            entries: &[BindGroupEntry {
-                binding: 0,
-                resource: properties_buffer.as_entire_binding(),
-            }],
-            label: None,
-            layout: &pipeline.material2d_layout,
-        });
         Ok(GpuWavesMaterial {
-            // properties_buffer,
-            // time_buffer,
+            properties_buffer,
             bind_group,
+            recycle: bindings.clone(),
         })
     }
 }
 
 impl Material2d for WavesMaterial {
+    fn vertex_shader(asset_server: &AssetServer) -> Option<Handle<Shader>> {
+        Some(asset_server.load(shader_paths::VERTEX))
+    }
+
     fn fragment_shader(asset_server: &AssetServer) -> Option<Handle<Shader>> {
-        Some(asset_server.load("shaders/fragment.spv"))
+        Some(asset_server.load(shader_paths::FRAGMENT))
     }
 
     fn bind_group(material: &<Self as RenderAsset>::PreparedAsset) -> &BindGroup {
@@ -120,21 +453,60 @@ impl Material2d for WavesMaterial {
 
     fn bind_group_layout(render_device: &RenderDevice) -> BindGroupLayout {
         render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
-            entries: &[BindGroupLayoutEntry {
-                binding: 0,
-                visibility: ShaderStages::FRAGMENT,
-                ty: BindingType::Buffer {
-                    ty: BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: BufferSize::new(Self::std140_size_static() as u64),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: BufferSize::new(Properties::SIZE as u64),
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: BufferSize::new(Globals::SIZE as u64),
+                    },
+                    count: None,
                 },
-                count: None,
-            }],
+            ],
             label: Some("waves bind group layout"),
         })
     }
 }
 
+fn extract_waves_globals(
+    mut commands: Commands,
+    time: Res<WavesTime>,
+    viewport: Res<WavesViewport>,
+    ripples: Res<Ripples>,
+    mesh: Res<WavesMesh>,
+) {
+    commands.insert_resource(*time);
+    commands.insert_resource(*viewport);
+    commands.insert_resource(*ripples);
+    commands.insert_resource(*mesh);
+}
+
+fn prepare_waves_globals(
+    globals: Res<WavesGlobals>,
+    time: Res<WavesTime>,
+    viewport: Res<WavesViewport>,
+    ripples: Res<Ripples>,
+    mesh: Res<WavesMesh>,
+    render_queue: Res<RenderQueue>,
+) {
+    let viewport = shaders::glam::Vec2::from(viewport.0.to_array());
+    let globals_uniform = Globals::new(time.0, viewport, mesh.columns, ripples.ripples);
+    render_queue.write_buffer(&globals.buffer, 0, bytemuck::bytes_of(&globals_uniform));
+}
+
+/// 同时插值所有层，材质的 `offset` 不变（它跟着水位走）。
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct WavesPropertiesLens {
     pub start: WavesMaterial,
@@ -144,14 +516,66 @@ pub struct WavesPropertiesLens {
 impl Lens<WavesMaterial> for WavesPropertiesLens {
     fn lerp(&mut self, target: &mut WavesMaterial, ratio: f32) {
         let Self { start, end } = self;
-        let color = start.color.lerp(end.color, ratio);
-        let frequency = start.frequency.lerp(&end.frequency, &ratio);
-        let amplitude = start.amplitude.lerp(&end.amplitude, &ratio);
-        let angular_velocity = start.angular_velocity.lerp(&end.angular_velocity, &ratio);
+        for ((target, start), end) in target
+            .layers_mut()
+            .into_iter()
+            .zip(start.layers())
+            .zip(end.layers())
+        {
+            *target = start.lerp(end, ratio);
+        }
+    }
+}
 
-        target.color = color;
-        target.frequency = frequency;
-        target.amplitude = amplitude;
-        target.angular_velocity = angular_velocity;
+/// 只插值一层，几个不同时长的 tween 放进 `Tracks` 就能让各层各自变化。
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct WaveLayerLens {
+    /// 从后往前数的第几层
+    pub layer: usize,
+    pub start: WaveLayer,
+    pub end: WaveLayer,
+}
+
+impl Lens<WavesMaterial> for WaveLayerLens {
+    fn lerp(&mut self, target: &mut WavesMaterial, ratio: f32) {
+        if let Some(layer) = target.layers_mut().into_iter().nth(self.layer) {
+            *layer = self.start.lerp(&self.end, ratio);
+        }
     }
 }
+
+/// 菜单和对局里波浪的颜色变化，后面的层变得更慢。
+pub fn waves_tween() -> Tracks<WavesMaterial> {
+    let start = WaveLayer {
+        amplitude: 0.2,
+        angular_velocity: 0.8,
+        frequency: 3.,
+        offset: 0.0,
+        color: Color::SEA_GREEN.into(),
+        deep_color: Color::rgb(0.02, 0.15, 0.25).into(),
+        ..Default::default()
+    };
+    let end = WaveLayer {
+        amplitude: 0.15,
+        angular_velocity: 0.8,
+        frequency: 3.5,
+        offset: 0.0,
+        color: Color::PINK.into(),
+        deep_color: Color::rgb(0.25, 0.05, 0.2).into(),
+        ..Default::default()
+    };
+
+    Tracks::new((0..LAYERS).map(|layer| {
+        let depth = (LAYERS - 1 - layer) as u8;
+        Tween::new(
+            EaseMethod::Linear,
+            TweeningType::PingPong,
+            Duration::from_secs_f32(15.0 + 5.0 * f32::from(depth)),
+            WaveLayerLens {
+                layer,
+                start: start.behind(depth),
+                end: end.behind(depth),
+            },
+        )
+    }))
+}
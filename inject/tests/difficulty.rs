@@ -0,0 +1,104 @@
+//! The difficulty presets are only read when the game starts, these check that every committed
+//! preset is playable and that waves past the end of a preset keep growing.
+
+use inject::difficulty::{Difficulty, DifficultyPreset, WaveSettings};
+
+fn load(preset: DifficultyPreset) -> Difficulty {
+    let path = format!("{}/assets/{}", env!("CARGO_MANIFEST_DIR"), preset.path());
+    let source = std::fs::read_to_string(&path).unwrap();
+    ron::from_str(&source).unwrap_or_else(|e| panic!("{}: {}", path, e))
+}
+
+fn wave(injects: u8) -> WaveSettings {
+    WaveSettings {
+        injects,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn presets_parse_and_validate() {
+    for preset in DifficultyPreset::ALL {
+        if let Err(e) = load(preset).validate() {
+            panic!("{}: {}", preset.path(), e);
+        }
+    }
+}
+
+#[test]
+fn default_matches_the_normal_preset() {
+    let normal = load(DifficultyPreset::Normal);
+    let default = Difficulty::default();
+    assert_eq!(default.waves, normal.waves);
+    assert_eq!(default.extra_injects, normal.extra_injects);
+}
+
+#[test]
+fn default_validates() {
+    Difficulty::default().validate().unwrap();
+}
+
+#[test]
+fn wave_repeats_the_last_one_with_extra_injects() {
+    let difficulty = Difficulty {
+        waves: vec![wave(3), wave(5)],
+        extra_injects: 2,
+    };
+    assert_eq!(difficulty.wave(0), wave(3));
+    assert_eq!(difficulty.wave(1), wave(3));
+    assert_eq!(difficulty.wave(2), wave(5));
+    assert_eq!(difficulty.wave(3), wave(7));
+    assert_eq!(difficulty.wave(5), wave(11));
+    assert_eq!(difficulty.wave(u8::MAX), wave(u8::MAX));
+}
+
+#[test]
+fn validate_rejects_unplayable_waves() {
+    let rejects = |waves: Vec<WaveSettings>| {
+        Difficulty {
+            waves,
+            extra_injects: 1,
+        }
+        .validate()
+        .is_err()
+    };
+
+    assert!(rejects(vec![]));
+    assert!(rejects(vec![wave(3), wave(0)]));
+    assert!(rejects(vec![WaveSettings {
+        init_radius: 60.0,
+        ..wave(3)
+    }]));
+    assert!(rejects(vec![WaveSettings {
+        init_radius: 0.0,
+        ..wave(3)
+    }]));
+    assert!(rejects(vec![WaveSettings {
+        growth: 2.0..2.0,
+        ..wave(3)
+    }]));
+
+    let mut no_kinds = wave(3);
+    no_kinds.kinds.normal = 0;
+    assert!(rejects(vec![no_kinds]));
+}
+
+#[test]
+fn from_ron_rejects_misspelled_fields() {
+    let source = std::fs::read_to_string(format!(
+        "{}/assets/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        DifficultyPreset::Normal.path()
+    ))
+    .unwrap();
+    Difficulty::from_ron(source.as_bytes()).unwrap();
+
+    for (field, typo) in [
+        ("extra_injects:", "extra_inject:"),
+        ("kinds:", "kind:"),
+        ("bomb:", "bombs:"),
+    ] {
+        let broken = source.replacen(field, typo, 1);
+        assert!(Difficulty::from_ron(broken.as_bytes()).is_err(), "{}", typo);
+    }
+}
@@ -0,0 +1,170 @@
+//! The high score file is read back on every start, these check that a broken or foreign file is
+//! rejected and set aside instead of being trusted.
+
+use std::{fs, path::PathBuf};
+
+use codec::Encode;
+use inject::{
+    difficulty::DifficultyPreset,
+    highscore::{decode, encode, HighScores, Record},
+    mode::GameMode,
+};
+
+fn record(score: u32) -> Record {
+    Record {
+        score,
+        duration: 60_000,
+        waves: 3,
+        unfair: false,
+        mode: GameMode::Classic,
+        difficulty: DifficultyPreset::Hard,
+    }
+}
+
+/// A fresh directory for one test, removed again by the test.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fishwar-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn round_trip() {
+    let records = vec![record(30), record(20), record(10)];
+    assert_eq!(decode(&encode(&records)).unwrap(), records);
+}
+
+#[test]
+fn wrong_version_is_rejected() {
+    let mut bytes = encode(&[record(10)]);
+    bytes[0] = bytes[0].wrapping_add(1);
+    assert!(decode(&bytes).is_err());
+}
+
+#[test]
+fn trailing_bytes_are_rejected() {
+    let mut bytes = encode(&[record(10)]);
+    bytes.push(0);
+    assert!(decode(&bytes).is_err());
+}
+
+#[test]
+fn oversized_input_is_rejected() {
+    let records: Vec<Record> = (0..41).map(record).collect();
+    assert!(decode(&encode(&records)).is_err());
+    assert!(decode(&[0; 4096]).is_err());
+}
+
+#[test]
+fn version_1_tables_are_taken_over() {
+    // score, duration, waves, unfair
+    let mut bytes = 1u8.encode();
+    vec![(30u32, 90_000u32, 5u8, true), (10, 20_000, 1, false)].encode_to(&mut bytes);
+
+    let records = decode(&bytes).unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(
+        records[0],
+        Record {
+            score: 30,
+            duration: 90_000,
+            waves: 5,
+            unfair: true,
+            mode: GameMode::Classic,
+            difficulty: DifficultyPreset::Normal,
+        }
+    );
+}
+
+#[test]
+fn insert_ranks_and_truncates() {
+    let dir = temp_dir("insert");
+    let mut high_scores = HighScores::load_from(dir.join("highscores.bin"));
+
+    high_scores.insert(record(20));
+    assert_eq!(high_scores.last_rank(), Some(0));
+    high_scores.insert(record(40));
+    assert_eq!(high_scores.last_rank(), Some(0));
+    high_scores.insert(record(30));
+    assert_eq!(high_scores.last_rank(), Some(1));
+
+    let scores = |high_scores: &HighScores| -> Vec<u32> {
+        high_scores
+            .records(GameMode::Classic)
+            .iter()
+            .map(|r| r.score)
+            .collect()
+    };
+    assert_eq!(scores(&high_scores), [40, 30, 20]);
+
+    for score in 50..60 {
+        high_scores.insert(record(score));
+    }
+    assert_eq!(high_scores.records(GameMode::Classic).len(), 10);
+    assert_eq!(scores(&high_scores)[9], 50);
+
+    // A run below a full table is not recorded, a tie goes below the older run.
+    high_scores.insert(record(10));
+    assert_eq!(high_scores.last_rank(), None);
+    high_scores.insert(record(55));
+    assert_eq!(high_scores.last_rank(), Some(5));
+    assert_eq!(high_scores.records(GameMode::Classic).len(), 10);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn modes_have_their_own_tables() {
+    let dir = temp_dir("modes");
+    let path = dir.join("highscores.bin");
+    let mut high_scores = HighScores::load_from(path.clone());
+
+    for score in 0..10 {
+        high_scores.insert(record(100 + score));
+    }
+    let zen = || Record {
+        mode: GameMode::Zen,
+        ..record(1)
+    };
+    high_scores.insert(zen());
+    assert_eq!(high_scores.last_rank(), Some(0));
+    assert_eq!(high_scores.records(GameMode::Zen), [zen()]);
+    assert_eq!(high_scores.records(GameMode::Classic).len(), 10);
+    assert!(high_scores.records(GameMode::TimeAttack).is_empty());
+
+    high_scores.save().unwrap();
+    let loaded = HighScores::load_from(path);
+    assert_eq!(loaded.records(GameMode::Zen), [zen()]);
+    assert_eq!(
+        loaded.records(GameMode::Classic),
+        high_scores.records(GameMode::Classic)
+    );
+    assert_eq!(loaded.last_rank(), None);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn corrupt_file_is_backed_up() {
+    let dir = temp_dir("corrupt");
+    let path = dir.join("highscores.bin");
+    fs::write(&path, b"not a high score table").unwrap();
+
+    let mut high_scores = HighScores::load_from(path.clone());
+    assert!(high_scores.records(GameMode::Classic).is_empty());
+    assert!(!path.exists());
+    assert_eq!(
+        fs::read(dir.join("highscores.bin.bak")).unwrap(),
+        b"not a high score table"
+    );
+
+    high_scores.insert(record(10));
+    high_scores.save().unwrap();
+    assert_eq!(
+        HighScores::load_from(path).records(GameMode::Classic),
+        [record(10)]
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,106 @@
+//! Inject kinds change when an inject pops and what popping it does, these check the kind rules
+//! and how a wave's weights pick between kinds.
+
+use inject::difficulty::{KindWeights, WaveSettings};
+use inject::inject::{InjectKind, GOLDEN_FACTOR, HEAVY_FACTOR};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+fn only(kind: InjectKind) -> KindWeights {
+    let mut weights = KindWeights {
+        normal: 0,
+        ..Default::default()
+    };
+    match kind {
+        InjectKind::Normal => weights.normal = 1,
+        InjectKind::Bomb => weights.bomb = 1,
+        InjectKind::Golden => weights.golden = 1,
+        InjectKind::Splitter => weights.splitter = 1,
+        InjectKind::Heavy => weights.heavy = 1,
+    }
+    weights
+}
+
+const KINDS: [InjectKind; 5] = [
+    InjectKind::Normal,
+    InjectKind::Bomb,
+    InjectKind::Golden,
+    InjectKind::Splitter,
+    InjectKind::Heavy,
+];
+
+#[test]
+fn pick_falls_back_to_normal_without_weights() {
+    let weights = KindWeights {
+        normal: 0,
+        ..Default::default()
+    };
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    for _ in 0..100 {
+        assert_eq!(weights.pick(&mut rng), InjectKind::Normal);
+    }
+}
+
+#[test]
+fn pick_only_returns_weighted_kinds() {
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    for kind in KINDS {
+        let weights = only(kind);
+        for _ in 0..100 {
+            assert_eq!(weights.pick(&mut rng), kind);
+        }
+    }
+}
+
+#[test]
+fn pick_reaches_every_weighted_kind() {
+    let weights = KindWeights {
+        normal: 1,
+        bomb: 1,
+        golden: 1,
+        splitter: 1,
+        heavy: 1,
+    };
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    let picked: Vec<_> = (0..200).map(|_| weights.pick(&mut rng)).collect();
+    for kind in KINDS {
+        assert!(picked.contains(&kind), "{:?} never picked", kind);
+    }
+}
+
+#[test]
+fn heavy_pops_later_and_golden_once_shrunk() {
+    let settings = WaveSettings::default();
+    let enough = settings.enough_radius;
+    let heavy = enough * HEAVY_FACTOR;
+    let golden = settings.init_radius * GOLDEN_FACTOR;
+
+    assert!(!InjectKind::Normal.is_ready(enough - 1.0, &settings));
+    assert!(InjectKind::Normal.is_ready(enough, &settings));
+
+    assert!(!InjectKind::Heavy.is_ready(enough, &settings));
+    assert!(!InjectKind::Heavy.is_ready(heavy - 1.0, &settings));
+    assert!(InjectKind::Heavy.is_ready(heavy, &settings));
+
+    assert!(!InjectKind::Golden.is_ready(enough, &settings));
+    assert!(!InjectKind::Golden.is_ready(golden + 1.0, &settings));
+    assert!(InjectKind::Golden.is_ready(golden, &settings));
+}
+
+#[test]
+fn bombs_raise_the_water_and_golden_lowers_it_twice() {
+    let drop = 0.01;
+    assert_eq!(InjectKind::Normal.water_drop(drop), drop);
+    assert_eq!(InjectKind::Splitter.water_drop(drop), drop);
+    assert_eq!(InjectKind::Heavy.water_drop(drop), drop);
+    assert_eq!(InjectKind::Bomb.water_drop(drop), -drop);
+    assert_eq!(InjectKind::Golden.water_drop(drop), drop * 2.0);
+}
+
+#[test]
+fn only_heavy_is_denser() {
+    for kind in KINDS {
+        let expected = if kind == InjectKind::Heavy { 3.0 } else { 1.0 };
+        assert_eq!(kind.density(), expected, "{:?}", kind);
+    }
+}
@@ -0,0 +1,68 @@
+//! Pausing pushes and pops a state from [`FrameInput`], these check that one press changes the
+//! state exactly once, although Bevy reruns `Update` in the frame a state changes.
+
+use bevy::{asset::AssetPlugin, prelude::*};
+use inject::{
+    game_state::FishWarState,
+    pause::PausePlugin,
+    replay::{FrameInput, InputSource},
+};
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin)
+        .insert_resource(InputSource::Live)
+        .init_resource::<FrameInput>()
+        .add_state(FishWarState::Game)
+        .add_plugin(PausePlugin);
+    app.update();
+    app
+}
+
+/// Runs one frame with `input` and returns the state it ends in.
+fn frame(app: &mut App, input: FrameInput) -> FishWarState {
+    *app.world.get_resource_mut::<FrameInput>().unwrap() = input;
+    app.update();
+    app.world
+        .get_resource::<State<FishWarState>>()
+        .unwrap()
+        .current()
+        .clone()
+}
+
+fn pause() -> FrameInput {
+    FrameInput {
+        pause: true,
+        ..Default::default()
+    }
+}
+
+fn back() -> FrameInput {
+    FrameInput {
+        back: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn one_press_pauses() {
+    let mut app = app();
+    assert_eq!(frame(&mut app, pause()), FishWarState::Paused);
+    assert_eq!(frame(&mut app, FrameInput::default()), FishWarState::Paused);
+}
+
+#[test]
+fn one_press_resumes() {
+    let mut app = app();
+    assert_eq!(frame(&mut app, pause()), FishWarState::Paused);
+    assert_eq!(frame(&mut app, pause()), FishWarState::Game);
+    assert_eq!(frame(&mut app, FrameInput::default()), FishWarState::Game);
+}
+
+#[test]
+fn escape_pauses_and_resumes() {
+    let mut app = app();
+    assert_eq!(frame(&mut app, back()), FishWarState::Paused);
+    assert_eq!(frame(&mut app, back()), FishWarState::Game);
+}
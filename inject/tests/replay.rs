@@ -0,0 +1,98 @@
+//! A recorded run has to feed back exactly the input it was played with, these check that
+//! nothing is lost on the way to the file and back.
+
+use bevy::math::Vec2;
+use inject::{
+    difficulty::Difficulty,
+    mode::GameMode,
+    replay::{FrameInput, RunLog},
+    victory::WinCondition,
+};
+
+fn new_log() -> RunLog {
+    RunLog::new(
+        42,
+        &Difficulty::default(),
+        WinCondition::SurviveWaves(10),
+        GameMode::TimeAttack,
+        Vec2::new(1280.0, 720.0),
+    )
+    .unwrap()
+}
+
+fn input(ticks: u8, cursor: Option<Vec2>) -> FrameInput {
+    FrameInput {
+        ticks,
+        cursor,
+        arena: Vec2::new(1280.0, 720.0),
+        ..Default::default()
+    }
+}
+
+/// A few frames with odd floats and every key, some of them repeated.
+fn inputs() -> Vec<FrameInput> {
+    let mut inputs = vec![input(1, None); 3];
+    inputs.push(input(2, Some(Vec2::new(0.1, -f32::MIN_POSITIVE))));
+    inputs.push(FrameInput {
+        unfair: true,
+        menu: true,
+        restart: true,
+        quit: true,
+        pause: true,
+        back: true,
+        ..input(0, Some(Vec2::new(-0.0, 1e30)))
+    });
+    inputs.extend(vec![input(1, Some(Vec2::new(3.5, 7.25))); 200]);
+    inputs.push(input(1, None));
+    inputs
+}
+
+#[test]
+fn round_trip() {
+    let mut log = new_log();
+    for input in inputs() {
+        log.push(&input);
+    }
+    assert_eq!(RunLog::from_bytes(&log.to_bytes()).unwrap(), log);
+}
+
+#[test]
+fn frames_expand_back_exactly() {
+    let mut log = new_log();
+    let inputs = inputs();
+    for input in &inputs {
+        log.push(input);
+    }
+    let replayed: Vec<FrameInput> = log.inputs().collect();
+    assert_eq!(replayed.len(), inputs.len());
+    for (replayed, input) in replayed.iter().zip(&inputs) {
+        // `-0.0 == 0.0`, compare the bits.
+        let bits = |input: &FrameInput| input.cursor.map(|c| (c.x.to_bits(), c.y.to_bits()));
+        assert_eq!(bits(replayed), bits(input));
+        assert_eq!(replayed, input);
+    }
+}
+
+#[test]
+fn identical_frames_are_merged() {
+    let mut once = new_log();
+    once.push(&input(1, None));
+    let mut many = new_log();
+    for _ in 0..1000 {
+        many.push(&input(1, None));
+    }
+    // Only the repeat count grows.
+    assert!(many.to_bytes().len() <= once.to_bytes().len() + 4);
+    assert_eq!(many.inputs().count(), 1000);
+}
+
+#[test]
+fn other_versions_and_trailing_bytes_are_rejected() {
+    let mut bytes = new_log().to_bytes();
+    bytes.push(0);
+    assert!(RunLog::from_bytes(&bytes).is_err());
+
+    let mut bytes = new_log().to_bytes();
+    bytes[0] = bytes[0].wrapping_sub(1);
+    assert!(RunLog::from_bytes(&bytes).is_err());
+}
@@ -0,0 +1,45 @@
+//! The simulation runs a fixed number of ticks per simulated second whatever the frame rate, these
+//! check how [`SimClock`] splits wall clock time into ticks.
+
+use std::time::Duration;
+
+use inject::sim::{SimClock, MAX_TICKS, SIM_STEP};
+
+/// Ticks run over one second of frames at the given rate.
+fn ticks_per_second(fps: u32) -> u32 {
+    let mut clock = SimClock::default();
+    let delta = Duration::from_secs(1) / fps;
+    (0..fps).map(|_| u32::from(clock.advance(delta))).sum()
+}
+
+#[test]
+fn frame_rate_does_not_change_the_tick_rate() {
+    for fps in [30, 60, 120] {
+        assert_eq!(ticks_per_second(fps), 60, "at {} fps", fps);
+    }
+}
+
+#[test]
+fn short_frames_carry_over() {
+    let mut clock = SimClock::default();
+    let half = SIM_STEP / 2;
+    assert_eq!(clock.advance(half), 0);
+    assert_eq!(clock.advance(half), 1);
+    assert_eq!(clock.advance(half), 0);
+}
+
+#[test]
+fn hitches_are_capped() {
+    let mut clock = SimClock::default();
+    assert_eq!(clock.advance(Duration::from_secs(1)), MAX_TICKS);
+    assert_eq!(clock.advance(Duration::ZERO), 0);
+    assert_eq!(clock.advance(SIM_STEP), 1);
+}
+
+#[test]
+fn reset_drops_the_carry() {
+    let mut clock = SimClock::default();
+    clock.advance(SIM_STEP / 2);
+    clock.reset();
+    assert_eq!(clock.advance(SIM_STEP / 2), 0);
+}
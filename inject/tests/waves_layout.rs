@@ -0,0 +1,214 @@
+//! `shaders::waves::{Properties, Globals}` are uploaded as raw bytes, these check that their
+//! `#[repr(C)]` layout is the std140 layout the waves bind group is created with.
+
+use bevy::{
+    math::{Vec2, Vec4},
+    render::render_resource::std140::{AsStd140, Std140},
+};
+use inject::waves::{WaveLayer, WavesMaterial};
+use shaders::waves::{Globals, Properties, Ripple, LAYERS};
+
+/// The uniform as std140 sees it, in the order `waves_frag` declares it.
+#[derive(AsStd140)]
+struct Reference {
+    back: LayerReference,
+    middle: LayerReference,
+    front: LayerReference,
+}
+
+#[derive(AsStd140)]
+struct LayerReference {
+    amplitude: f32,
+    angular_velocity: f32,
+    frequency: f32,
+    offset: f32,
+    color: Vec4,
+    steepness: f32,
+    direction: f32,
+    foam_width: f32,
+    edge_softness: f32,
+    foam_color: Vec4,
+    deep_color: Vec4,
+}
+
+/// crevice has no arrays, one member per ripple gives the same 16 byte stride. It also pads
+/// `columns` up to the first ripple like the `_padding` fields do.
+#[derive(AsStd140)]
+struct GlobalsReference {
+    time: f32,
+    aspect: f32,
+    viewport: Vec2,
+    columns: f32,
+    ripple_0: RippleReference,
+    ripple_1: RippleReference,
+    ripple_2: RippleReference,
+    ripple_3: RippleReference,
+    ripple_4: RippleReference,
+    ripple_5: RippleReference,
+    ripple_6: RippleReference,
+    ripple_7: RippleReference,
+}
+
+#[derive(AsStd140)]
+struct RippleReference {
+    position: Vec2,
+    start: f32,
+    strength: f32,
+}
+
+fn offset_of<T>(base: &Properties, field: &T) -> usize {
+    field as *const T as usize - base as *const Properties as usize
+}
+
+/// Offsets of scalar and vec4 members under std140: scalars align to 4, vectors of four to 16.
+fn std140_offsets(members: &[usize]) -> Vec<usize> {
+    let mut offset = 0;
+    members
+        .iter()
+        .map(|&size| {
+            let align = if size == 16 { 16 } else { 4 };
+            offset = (offset + align - 1) / align * align;
+            let member = offset;
+            offset += size;
+            member
+        })
+        .collect()
+}
+
+/// Every field gets a different value, so a shifted one shows up.
+fn layer(first: f32) -> (WaveLayer, LayerReference) {
+    let vec4 = |from: f32| Vec4::new(from, from + 1.0, from + 2.0, from + 3.0);
+    (
+        WaveLayer {
+            amplitude: first,
+            angular_velocity: first + 1.0,
+            frequency: first + 2.0,
+            offset: first + 3.0,
+            color: vec4(first + 4.0),
+            steepness: first + 8.0,
+            direction: first + 9.0,
+            foam_width: first + 10.0,
+            edge_softness: first + 11.0,
+            foam_color: vec4(first + 12.0),
+            deep_color: vec4(first + 16.0),
+        },
+        LayerReference {
+            amplitude: first,
+            angular_velocity: first + 1.0,
+            frequency: first + 2.0,
+            offset: first + 3.0,
+            color: vec4(first + 4.0),
+            steepness: first + 8.0,
+            direction: first + 9.0,
+            foam_width: first + 10.0,
+            edge_softness: first + 11.0,
+            foam_color: vec4(first + 12.0),
+            deep_color: vec4(first + 16.0),
+        },
+    )
+}
+
+#[test]
+fn size_matches_std140() {
+    assert_eq!(std::mem::size_of::<Properties>(), Properties::SIZE);
+    assert_eq!(Reference::std140_size_static(), Properties::SIZE);
+    assert_eq!(Properties::SIZE % 16, 0);
+
+    assert_eq!(std::mem::size_of::<Globals>(), Globals::SIZE);
+    assert_eq!(GlobalsReference::std140_size_static(), Globals::SIZE);
+}
+
+#[test]
+fn offsets_match_std140() {
+    let properties = WavesMaterial::default().properties();
+    let stride = LayerReference::std140_size_static();
+    assert_eq!(properties.layers.len(), LAYERS);
+
+    for (i, layer) in properties.layers.iter().enumerate() {
+        let offsets = [
+            offset_of(&properties, &layer.amplitude),
+            offset_of(&properties, &layer.angular_velocity),
+            offset_of(&properties, &layer.frequency),
+            offset_of(&properties, &layer.offset),
+            offset_of(&properties, &layer.color),
+            offset_of(&properties, &layer.steepness),
+            offset_of(&properties, &layer.direction),
+            offset_of(&properties, &layer.foam_width),
+            offset_of(&properties, &layer.edge_softness),
+            offset_of(&properties, &layer.foam_color),
+            offset_of(&properties, &layer.deep_color),
+        ];
+        let expected: Vec<usize> = std140_offsets(&[4, 4, 4, 4, 16, 4, 4, 4, 4, 16, 16])
+            .into_iter()
+            .map(|offset| offset + i * stride)
+            .collect();
+
+        assert_eq!(offsets.to_vec(), expected, "layer {}", i);
+    }
+}
+
+#[test]
+fn bytes_match_std140() {
+    let (back, back_reference) = layer(10.0);
+    let (middle, middle_reference) = layer(20.0);
+    let (front, front_reference) = layer(30.0);
+    // The material offset is added to every layer, keep it out of the comparison.
+    let material = WavesMaterial {
+        offset: 0.0,
+        back,
+        middle,
+        front,
+    };
+    let reference = Reference {
+        back: back_reference,
+        middle: middle_reference,
+        front: front_reference,
+    };
+
+    assert_eq!(
+        bytemuck::bytes_of(&material.properties()),
+        reference.as_std140().as_bytes()
+    );
+
+    let ripple = |first: f32| {
+        (
+            Ripple {
+                position: shaders::glam::Vec2::new(first, first + 1.0),
+                start: first + 2.0,
+                strength: first + 3.0,
+            },
+            RippleReference {
+                position: Vec2::new(first, first + 1.0),
+                start: first + 2.0,
+                strength: first + 3.0,
+            },
+        )
+    };
+    let firsts = [0.0, 10.0, 20.0, 30.0, 40.0, 50.0, 60.0, 70.0];
+    let [ripple_0, ripple_1, ripple_2, ripple_3, ripple_4, ripple_5, ripple_6, ripple_7] =
+        firsts.map(|first| ripple(first).1);
+    let globals_reference = GlobalsReference {
+        time: 9.0,
+        aspect: 2.0,
+        viewport: Vec2::new(1600.0, 800.0),
+        columns: 4.0,
+        ripple_0,
+        ripple_1,
+        ripple_2,
+        ripple_3,
+        ripple_4,
+        ripple_5,
+        ripple_6,
+        ripple_7,
+    };
+    let globals = Globals::new(
+        9.0,
+        shaders::glam::Vec2::new(1600.0, 800.0),
+        4,
+        firsts.map(|first| ripple(first).0),
+    );
+    assert_eq!(
+        bytemuck::bytes_of(&globals),
+        globals_reference.as_std140().as_bytes()
+    );
+}
@@ -1,51 +1,180 @@
+//! 用 rust-gpu 把 `shaders` 编译成 SPIR-V，每个入口一个模块。
+//!
+//! 目标环境、capability、扩展、输出目录和入口到文件名的对应关系都在 `shaders.ron` 里：
+//!
+//! ```sh
+//! cargo +nightly-2022-01-13 run -r -p shaders-builder -- --capability Int8 --out-dir /tmp/shaders
+//! ```
+//!
+//! 参数：`--manifest <path>`（默认是本目录下的 `shaders.ron`）、`--target <target>`、
+//! `--out-dir <dir>`，以及可以重复的 `--capability <name>` 和 `--extension <name>`。
+
+use serde::Deserialize;
 use spirv_builder::{Capability, MetadataPrintout, SpirvBuilder};
-use std::{error::Error, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let builder_root = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+/// `shaders.ron` 的内容，其中的路径相对于清单所在的目录。
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    target: String,
+    capabilities: Vec<String>,
+    extensions: Vec<String>,
+    out_dir: PathBuf,
+    /// `out_dir` 相对于 `inject/assets` 的位置，生成资源路径用
+    asset_dir: String,
+    /// 入口名 -> 输出的文件名（不含扩展名）
+    entry_points: BTreeMap<String, String>,
+    paths_module: PathBuf,
+}
 
-    let extensions = &[];
+impl Manifest {
+    fn from_args(builder_root: &Path) -> Result<Self, Box<dyn Error>> {
+        let mut args = std::env::args().skip(1);
+        let mut manifest_path = builder_root.join("shaders.ron");
+        let mut overrides = Vec::new();
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("{} needs a value", arg))?;
+            match arg.as_str() {
+                "--manifest" => manifest_path = PathBuf::from(value),
+                "--target" | "--capability" | "--extension" | "--out-dir" => {
+                    overrides.push((arg, value))
+                }
+                _ => return Err(format!("unknown argument {:?}", arg).into()),
+            }
+        }
 
-    let capabilities = &[];
+        let mut manifest: Self = ron::de::from_bytes(&fs::read(&manifest_path)?)
+            .map_err(|e| format!("{}: {}", manifest_path.display(), e))?;
+        let manifest_dir = manifest_path.parent().unwrap_or_else(|| Path::new("."));
+        manifest.out_dir = manifest_dir.join(&manifest.out_dir);
+        manifest.paths_module = manifest_dir.join(&manifest.paths_module);
 
-    compile_shader_multi(&builder_root, extensions, capabilities)?;
+        for (arg, value) in overrides {
+            match arg.as_str() {
+                "--target" => manifest.target = value,
+                "--capability" => manifest.capabilities.push(value),
+                "--extension" => manifest.extensions.push(value),
+                "--out-dir" => manifest.out_dir = PathBuf::from(value),
+                _ => unreachable!(),
+            }
+        }
+        Ok(manifest)
+    }
+
+    fn capabilities(&self) -> Result<Vec<Capability>, Box<dyn Error>> {
+        self.capabilities
+            .iter()
+            .map(|name| {
+                name.parse::<Capability>()
+                    .map_err(|_| format!("unknown capability {:?}", name).into())
+            })
+            .collect()
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let builder_root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let manifest = Manifest::from_args(builder_root)?;
+
+    let modules = compile_shader_multi(&builder_root.join("../shaders/"), &manifest)?;
+    write_paths_module(&manifest, &modules)?;
 
     Ok(())
 }
 
+/// 编译并把每个入口的模块复制到 `out_dir`，返回入口名 -> 文件名。
 fn compile_shader_multi(
-    root_path: &Path,
-    extensions: &[&str],
-    capabilities: &[Capability],
-) -> Result<(), Box<dyn Error>> {
-    let mut builder = SpirvBuilder::new(
-        root_path.join("../shaders/"),
-        "spirv-unknown-vulkan1.1spv1.4",
-    )
-    .print_metadata(MetadataPrintout::DependencyOnly)
-    .multimodule(true);
+    shader_crate: &Path,
+    manifest: &Manifest,
+) -> Result<BTreeMap<String, String>, Box<dyn Error>> {
+    let mut builder = SpirvBuilder::new(shader_crate, &manifest.target)
+        .print_metadata(MetadataPrintout::DependencyOnly)
+        .multimodule(true);
 
-    for extension in extensions {
-        builder = builder.extension(*extension);
+    for extension in &manifest.extensions {
+        builder = builder.extension(extension);
     }
 
-    for capability in capabilities {
-        builder = builder.capability(*capability);
+    for capability in manifest.capabilities()? {
+        builder = builder.capability(capability);
     }
 
     let result = builder.build()?;
+    let modules: &HashMap<String, PathBuf> = result.module.unwrap_multi();
 
-    for (name, path) in result.module.unwrap_multi() {
-        let name = handle_name(name);
-        std::fs::copy(
-            path,
-            root_path.join(&format!("../inject/assets/shaders/{}.spv", name)),
-        )?;
+    if let Some(missing) = manifest
+        .entry_points
+        .keys()
+        .find(|entry| !modules.contains_key(*entry))
+    {
+        return Err(format!(
+            "entry point {:?} is in the manifest but was not built",
+            missing
+        )
+        .into());
     }
 
-    Ok(())
+    fs::create_dir_all(&manifest.out_dir)?;
+    let mut written = BTreeMap::new();
+    for (entry, path) in modules.iter().collect::<BTreeMap<_, _>>() {
+        let name = handle_name(manifest, entry)?;
+        let target = manifest.out_dir.join(format!("{}.spv", name));
+        fs::copy(path, &target)?;
+        println!("wrote {}", target.display());
+        written.insert(entry.clone(), name.to_string());
+    }
+
+    Ok(written)
+}
+
+fn handle_name<'a>(manifest: &'a Manifest, entry: &str) -> Result<&'a str, Box<dyn Error>> {
+    manifest
+        .entry_points
+        .get(entry)
+        .map(String::as_str)
+        .ok_or_else(|| {
+            format!(
+                "entry point {:?} has no file name, add it to `entry_points` in the manifest",
+                entry
+            )
+            .into()
+        })
 }
 
-fn handle_name(name: &str) -> &str {
-    name
+/// 写出列出资源路径的 Rust 模块，内容没变时不动它，免得 `inject` 重新编译。
+fn write_paths_module(
+    manifest: &Manifest,
+    modules: &BTreeMap<String, String>,
+) -> Result<(), Box<dyn Error>> {
+    let mut source = String::from(
+        "//! 着色器的资源路径，由 `shaders-builder` 按 `shaders-builder/shaders.ron` 生成，不要手动修改。\n\
+         //!\n\
+         //! 原生平台加载 SPIR-V，网页版（WebGL2）加载 `cargo gen-shader` 翻译出的 WGSL。\n",
+    );
+    for (entry, name) in modules {
+        let constant = entry.to_uppercase();
+        for (cfg, extension) in [
+            ("not(target_arch = \"wasm32\")", "spv"),
+            ("target_arch = \"wasm32\"", "wgsl"),
+        ] {
+            source.push_str(&format!(
+                "\n#[cfg({})]\npub const {}: &str = \"{}/{}.{}\";",
+                cfg, constant, manifest.asset_dir, name, extension
+            ));
+        }
+        source.push('\n');
+    }
+
+    if fs::read_to_string(&manifest.paths_module).ok().as_deref() != Some(source.as_str()) {
+        fs::write(&manifest.paths_module, &source)?;
+        println!("wrote {}", manifest.paths_module.display());
+    }
+    Ok(())
 }
@@ -6,4 +6,8 @@
 )]
 #![allow(clippy::too_many_arguments)]
 
+#[cfg(not(target_arch = "spirv"))]
+pub mod reference;
 pub mod waves;
+
+pub use spirv_std::glam;
@@ -0,0 +1,73 @@
+//! 着色器的 CPU 参考实现：在主机上逐像素调用片元函数，得到和 GPU 上一样的画面。
+//!
+//! 用来在没有 GPU 的机器上查看、测试着色器的输出，见 `tests/golden.rs`。
+
+use spirv_std::glam::{Vec2, Vec3, Vec4};
+
+use crate::waves::{waves_frag, Globals, Properties};
+
+/// RGBA8 图像，像素逐行从上到下排列。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// 渲染铺满整张图的波浪四边形，和 `new_waves_mesh` 一样 uv 的 y 轴朝下。
+    ///
+    /// 着色器提前返回的像素保持透明；颜色直接截断到 `[0, 1]`，不做 sRGB 转换。
+    pub fn render_waves(
+        properties: &Properties,
+        globals: &Globals,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+        for y in 0..height {
+            for x in 0..width {
+                let frag_coord = Vec4::new(x as f32 + 0.5, y as f32 + 0.5, 0.0, 1.0);
+                let uv = Vec2::new(frag_coord.x / width as f32, frag_coord.y / height as f32);
+
+                let mut output = Vec4::ZERO;
+                waves_frag(
+                    Vec4::ZERO,
+                    Vec3::Z,
+                    uv,
+                    frag_coord,
+                    properties,
+                    globals,
+                    &mut output,
+                );
+                pixels.extend(
+                    output
+                        .to_array()
+                        .map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8),
+                );
+            }
+        }
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// 两张图所有通道里最大的差值，尺寸不同时返回 `None`。
+    pub fn max_difference(&self, other: &Self) -> Option<u8> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return None;
+        }
+
+        Some(
+            self.pixels
+                .iter()
+                .zip(&other.pixels)
+                .map(|(a, b)| a.max(b) - a.min(b))
+                .max()
+                .unwrap_or(0),
+        )
+    }
+}
@@ -7,15 +7,42 @@
 #[cfg(not(target_arch = "spirv"))]
 use spirv_std::macros::spirv;
 
-use spirv_std::glam::{Vec2, Vec3, Vec4, Vec4Swizzles};
+use spirv_std::glam::{Mat4, Vec2, Vec3, Vec4, Vec4Swizzles};
 
 #[cfg(target_arch = "spirv")]
 use spirv_std::num_traits::Float;
 
+/// 一个材质里的波浪层数。
+pub const LAYERS: usize = 3;
+
+/// 同时存在的涟漪数，新的涟漪覆盖最旧的。
+pub const RIPPLES: usize = 8;
+/// 涟漪持续的秒数。
+pub const RIPPLE_LIFETIME: f32 = 3.0;
+/// 涟漪向两边扩散的速度（视口高度/秒）。
+const RIPPLE_SPEED: f32 = 0.3;
+/// 涟漪波包的宽度（视口高度）。
+const RIPPLE_WIDTH: f32 = 0.08;
+/// 涟漪波包里波纹的密度。
+const RIPPLE_WAVENUMBER: f32 = 60.0;
+
+/// 波浪材质的 uniform，着色器和 `inject` 的 `WavesMaterial` 共用这一个定义。
+///
+/// 字段按 std140 排列：每层 80 字节，正好是数组要求的 16 字节步长的倍数，
+/// 所以 `#[repr(C)]` 的内存布局可以直接上传给 GPU。
 #[derive(Copy, Clone)]
 #[cfg_attr(not(target_arch = "spirv"), derive(Debug, PartialEq))]
 #[repr(C)]
 pub struct Properties {
+    /// 从后往前绘制的波浪层
+    pub layers: [Layer; LAYERS],
+}
+
+/// 一层波浪，`color.w` 是这一层的不透明度。
+#[derive(Copy, Clone)]
+#[cfg_attr(not(target_arch = "spirv"), derive(Debug, PartialEq))]
+#[repr(C)]
+pub struct Layer {
     /// 振幅（控制波浪顶端和底端的高度）
     ///
     /// 曲线最高点与最低点的差值，表现为曲线的整体高度
@@ -30,8 +57,275 @@ pub struct Properties {
     pub offset: f32,
     // 底色
     pub color: Vec4,
+    /// 陡度（0 是圆滑的正弦，接近 1 时波峰变尖）
+    pub steepness: f32,
+    /// 移动方向（1 向左，-1 向右）
+    pub direction: f32,
+    /// 波峰下泡沫带的宽度（uv 单位，0 表示没有泡沫）
+    pub foam_width: f32,
+    /// 水面边缘的过渡宽度（uv 单位），用来抗锯齿
+    pub edge_softness: f32,
+    /// 泡沫颜色，`w` 是泡沫的浓度
+    pub foam_color: Vec4,
+    /// 最底部的颜色，从水面往下由 `color` 渐变过去，`w` 不用
+    pub deep_color: Vec4,
+}
+
+/// 所有波浪共用的 uniform，每帧都会变的值放在这里，材质只在参数真正变化时更新。
+#[derive(Copy, Clone)]
+#[cfg_attr(not(target_arch = "spirv"), derive(Debug, PartialEq))]
+#[repr(C)]
+pub struct Globals {
     // 时间
     pub time: f32,
+    /// 视口的宽高比，波浪的横坐标按它拉伸，宽屏上的波形不会被压扁
+    pub aspect: f32,
+    /// 视口的物理像素大小，`frag_coord` 除以它就和分辨率、DPI 无关了
+    pub viewport: Vec2,
+    /// 波浪网格的列数，大于 1 时顶点着色器把顶边移到水面上
+    pub columns: f32,
+    // 补齐到 16 字节，涟漪数组要从 16 的倍数开始
+    pub _padding_0: f32,
+    pub _padding_1: f32,
+    pub _padding_2: f32,
+    /// 涟漪的环形缓冲区，`strength` 为 0 的位置是空的
+    pub ripples: [Ripple; RIPPLES],
+}
+
+/// 一个涟漪：从 `position` 处向左右扩散、逐渐消失的一串波纹。
+///
+/// 16 字节，正好是 uniform 数组的步长。
+#[derive(Copy, Clone, Default)]
+#[cfg_attr(not(target_arch = "spirv"), derive(Debug, PartialEq))]
+#[repr(C)]
+pub struct Ripple {
+    /// 波浪四边形上的 uv 坐标，离水面越远涟漪越弱
+    pub position: Vec2,
+    /// 开始的时间，和 `Globals::time` 同一个时钟
+    pub start: f32,
+    /// 最大的高度（uv 单位）
+    pub strength: f32,
+}
+
+/// Bevy 的视图 uniform，只声明用得到的开头部分。
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct View {
+    pub view_proj: Mat4,
+}
+
+/// Bevy 的 2D 网格 uniform，只声明用得到的开头部分。
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct Mesh2d {
+    pub model: Mat4,
+    pub inverse_transpose_model: Mat4,
+}
+
+#[cfg(not(target_arch = "spirv"))]
+impl Properties {
+    /// std140 下的大小，也是 bind group layout 的 `min_binding_size`。
+    pub const SIZE: usize = 80 * LAYERS;
+}
+
+#[cfg(not(target_arch = "spirv"))]
+impl Globals {
+    /// std140 下的大小，也是 bind group layout 的 `min_binding_size`。
+    pub const SIZE: usize = 32 + 16 * RIPPLES;
+
+    /// 窗口还没有大小（最小化）时按 1×1 处理，着色器里不会除以零。
+    pub fn new(time: f32, viewport: Vec2, columns: u32, ripples: [Ripple; RIPPLES]) -> Self {
+        let viewport = viewport.max(Vec2::ONE);
+        Self {
+            time,
+            aspect: viewport.x / viewport.y,
+            viewport,
+            columns: columns.max(1) as f32,
+            _padding_0: 0.0,
+            _padding_1: 0.0,
+            _padding_2: 0.0,
+            ripples,
+        }
+    }
+}
+
+// 确认没有编译器插入的填充，`Pod` 才成立。
+#[cfg(not(target_arch = "spirv"))]
+const _: () = assert!(core::mem::size_of::<Layer>() == 80);
+#[cfg(not(target_arch = "spirv"))]
+const _: () = assert!(core::mem::size_of::<Ripple>() == 16);
+#[cfg(not(target_arch = "spirv"))]
+const _: () = assert!(core::mem::size_of::<Properties>() == Properties::SIZE);
+#[cfg(not(target_arch = "spirv"))]
+const _: () = assert!(core::mem::size_of::<Globals>() == Globals::SIZE);
+
+// SAFETY: `#[repr(C)]`，字段全是 `f32`（`Vec4` 是 4 个 `f32`），上面的断言保证没有填充字节。
+#[cfg(not(target_arch = "spirv"))]
+unsafe impl bytemuck::Zeroable for Layer {}
+#[cfg(not(target_arch = "spirv"))]
+unsafe impl bytemuck::Pod for Layer {}
+#[cfg(not(target_arch = "spirv"))]
+unsafe impl bytemuck::Zeroable for Properties {}
+#[cfg(not(target_arch = "spirv"))]
+unsafe impl bytemuck::Pod for Properties {}
+#[cfg(not(target_arch = "spirv"))]
+unsafe impl bytemuck::Zeroable for Ripple {}
+#[cfg(not(target_arch = "spirv"))]
+unsafe impl bytemuck::Pod for Ripple {}
+#[cfg(not(target_arch = "spirv"))]
+unsafe impl bytemuck::Zeroable for Globals {}
+#[cfg(not(target_arch = "spirv"))]
+unsafe impl bytemuck::Pod for Globals {}
+
+/// 一个 Gerstner 分量在 `x0` 处的（高度，水平位移），高度和 uv 一样向下为正。
+///
+/// 位移按分量数和波数缩小，这样 `steepness` 到 1 时波峰正好变尖而不会打结。
+fn gerstner(layer: &Layer, x0: f32, time: f32, wavenumber: f32, scale: f32, phase: f32) -> Vec2 {
+    // 初相位（正值表现为向左移动，负值则表现为向右移动），越细碎的波走得越慢
+    let initia_phase = layer.direction * layer.frequency * time * wavenumber.sqrt();
+    let theta = wavenumber * x0 + layer.angular_velocity + phase + initia_phase;
+
+    Vec2::new(
+        layer.amplitude * scale * theta.sin(),
+        layer.steepness / (wavenumber * 3.0) * theta.cos(),
+    )
+}
+
+/// 三个波数不同的 Gerstner 波叠加在一起。
+fn gerstner_sum(layer: &Layer, x0: f32, time: f32) -> Vec2 {
+    gerstner(layer, x0, time, 1.0, 1.0, 0.0)
+        + gerstner(layer, x0, time, 3.0, 0.3, 1.7)
+        + gerstner(layer, x0, time, 7.0, 0.1, 4.1)
+}
+
+/// 水面在 `x` 处相对偏距的高度（向下为正）。
+///
+/// Gerstner 波会把水面沿水平方向挤压，先迭代几次找回 `x` 处的水原来在哪里。
+pub fn surface_height(layer: &Layer, x: f32, time: f32) -> f32 {
+    let mut x0 = x;
+    let mut i = 0;
+    while i < 4 {
+        x0 = x + gerstner_sum(layer, x0, time).y;
+        i += 1;
+    }
+    gerstner_sum(layer, x0, time).x
+}
+
+/// 涟漪还剩下的强度比例，还没开始或者已经消失的是 0。
+fn ripple_life(ripple: &Ripple, time: f32) -> f32 {
+    let age = time - ripple.start;
+    if ripple.strength <= 0.0 || age < 0.0 {
+        return 0.0;
+    }
+    (1.0 - age / RIPPLE_LIFETIME).max(0.0)
+}
+
+/// 所有涟漪在 `x`（以视口高度为单位）处给水面 `surface`（uv 的 y）带来的高度。
+pub fn ripple_height(globals: &Globals, x: f32, surface: f32) -> f32 {
+    let mut height = 0.0;
+    let mut i = 0;
+    while i < RIPPLES {
+        let ripple = &globals.ripples[i];
+        i += 1;
+
+        let life = ripple_life(ripple, globals.time);
+        if life == 0.0 {
+            continue;
+        }
+
+        // 到波包中心的距离，波包以固定的速度向两边走
+        let age = globals.time - ripple.start;
+        let distance = (x - ripple.position.x * globals.aspect).abs() - RIPPLE_SPEED * age;
+        let envelope = (-(distance * distance) / (RIPPLE_WIDTH * RIPPLE_WIDTH)).exp();
+        let fade = life * (-(ripple.position.y - surface).abs() * 4.0).exp();
+        height += ripple.strength * envelope * fade * (distance * RIPPLE_WAVENUMBER).cos();
+    }
+    height
+}
+
+/// 所有还在的涟漪最多能让水面升高多少。
+fn ripple_bound(globals: &Globals) -> f32 {
+    let mut bound = 0.0;
+    let mut i = 0;
+    while i < RIPPLES {
+        let ripple = &globals.ripples[i];
+        i += 1;
+
+        if ripple_life(ripple, globals.time) > 0.0 {
+            bound += ripple.strength;
+        }
+    }
+    bound
+}
+
+/// 细分网格的顶边在 `u` 处要放到的 uv 高度，保证两个顶点之间所有层的水面都在顶边下面。
+///
+/// 没有可见的层时返回 1（网格缩成底边上的一条线，什么也不画）。
+pub fn strip_top(properties: &Properties, globals: &Globals, u: f32) -> f32 {
+    let x = u * globals.aspect;
+    // 半列宽，单位和 `x` 一样是视口高度
+    let half = 0.5 * globals.aspect / globals.columns;
+    let ripples = ripple_bound(globals);
+
+    let mut top: f32 = 1.0;
+    let mut i = 0;
+    while i < LAYERS {
+        let layer = &properties.layers[i];
+        i += 1;
+
+        if layer.color.w == 0.0 {
+            continue;
+        }
+
+        let time = globals.time;
+        let surface = surface_height(layer, x - half, time)
+            .min(surface_height(layer, x, time))
+            .min(surface_height(layer, x + half, time));
+        // 采样点之间的水面可能更高，按最大斜率留出余量：三个分量的斜率之和是 2.6 倍振幅，
+        // Gerstner 的挤压最多再放大 1 / (1 - steepness) 倍
+        let slope = layer.amplitude.abs() * 2.6 / (1.0 - layer.steepness).max(0.1);
+        let edge = layer.offset + surface - slope * half - layer.edge_softness.max(0.0) - ripples;
+        top = top.min(edge);
+    }
+    top.max(0.0)
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// 波浪网格的顶点着色器，和 Bevy 默认的 2D 网格着色器一样，只是细分网格的顶边会移到水面上，
+/// 水面以上不再有要丢弃的像素。
+#[allow(clippy::too_many_arguments)]
+#[spirv(vertex(entry_point_name = "vertex"))]
+pub fn waves_vert(
+    position: Vec3,
+    normal: Vec3,
+    uv: Vec2,
+    #[spirv(uniform, descriptor_set = 0, binding = 0)] view: &View,
+    #[spirv(uniform, descriptor_set = 1, binding = 0)] properties: &Properties,
+    #[spirv(uniform, descriptor_set = 1, binding = 1)] globals: &Globals,
+    #[spirv(uniform, descriptor_set = 2, binding = 0)] mesh: &Mesh2d,
+    #[spirv(position)] out_clip_position: &mut Vec4,
+    out_world_position: &mut Vec4,
+    out_world_normal: &mut Vec3,
+    out_uv: &mut Vec2,
+) {
+    let mut position = position;
+    let mut uv = uv;
+    if globals.columns > 1.0 && uv.y <= 0.0 {
+        let top = strip_top(properties, globals, uv.x);
+        // 网格以原点为中心，顶边在高度的一半处
+        position.y *= 1.0 - 2.0 * top;
+        uv.y = top;
+    }
+
+    let world_position = mesh.model * position.extend(1.0);
+    *out_world_position = world_position;
+    *out_world_normal = (mesh.inverse_transpose_model * normal.extend(0.0)).xyz();
+    *out_uv = uv;
+    *out_clip_position = view.view_proj * world_position;
 }
 
 #[spirv(fragment(entry_point_name = "fragment"))]
@@ -41,28 +335,65 @@ pub fn waves_frag(
     uv: Vec2,
     #[spirv(frag_coord)] in_frag_coord: Vec4,
     #[spirv(uniform, descriptor_set = 1, binding = 0)] properties: &Properties,
+    #[spirv(uniform, descriptor_set = 1, binding = 1)] globals: &Globals,
     output: &mut Vec4,
 ) {
-    // 直接丢弃原本就透明的像素
-    if properties.color.w == 0.0 {
-        return;
-    }
+    // 从最后面的一层开始，把水面以下的部分逐层叠上去（预乘 alpha）
+    let mut color = Vec4::ZERO;
+    let mut i = 0;
+    while i < LAYERS {
+        let layer = &properties.layers[i];
+        i += 1;
+
+        // 直接跳过原本就透明的层
+        if layer.color.w == 0.0 {
+            continue;
+        }
+
+        let x = uv.x * globals.aspect;
+        let height =
+            surface_height(layer, x, globals.time) + ripple_height(globals, x, layer.offset);
+        // 到水面的距离，水面以下为正
+        let depth = uv.y - (height + layer.offset);
 
-    // 初相位（正值表现为向左移动，负值则表现为向右移动）
-    // cc_time 是 Cocos Creator 提供的运行时间全局变量（类型：vec4）
-    let initia_phase = properties.frequency * properties.time;
+        // 边缘在 edge_softness 内渐变，而不是一刀切
+        let coverage = if layer.edge_softness > 0.0 {
+            smoothstep(-layer.edge_softness, layer.edge_softness, depth)
+        } else if depth >= 0.0 {
+            1.0
+        } else {
+            0.0
+        };
+        if coverage == 0.0 {
+            continue;
+        }
 
-    // 代入正弦曲线公式计算 y 值
-    // y = Asin(ωx ± φt) + k
-    let y = properties.amplitude * (properties.angular_velocity + uv.x + initia_phase).sin()
-        + properties.offset;
+        // 越往下越接近 deep_color
+        let bottom = (1.0 - (height + layer.offset)).max(0.001);
+        let gradient = (depth.max(0.0) / bottom).clamp(0.0, 1.0);
+        let mut base = layer.color.xyz().lerp(layer.deep_color.xyz(), gradient);
 
-    if uv.y < y {
-        return;
+        // 波峰（高度最小处）下面的一条泡沫
+        if layer.foam_width > 0.0 {
+            let crest = (-height / (layer.amplitude * 1.4).max(0.001) * 0.5 + 0.5).clamp(0.0, 1.0);
+            let band = 1.0 - smoothstep(0.0, layer.foam_width, depth.max(0.0));
+            base = base.lerp(
+                layer.foam_color.xyz(),
+                band * crest * crest * layer.foam_color.w,
+            );
+        }
+
+        // 这一层的颜色，按不透明度盖在后面的层上
+        // 以视口高度为单位的屏幕坐标，换了分辨率颜色也不变
+        let screen = in_frag_coord.xy() / globals.viewport.y;
+        let tint = base.lerp(screen.extend(0.0).normalize(), 0.2);
+        let alpha = layer.color.w * coverage;
+        color =
+            (tint * alpha + color.xyz() * (1.0 - alpha)).extend(alpha + color.w * (1.0 - alpha));
     }
 
-    // 输出颜色
-    *output = properties
-        .color
-        .lerp(in_frag_coord.xyz().normalize().extend(1.0), 0.2);
+    // 输出颜色，还原成非预乘的 alpha
+    if color.w > 0.0 {
+        *output = (color.xyz() / color.w).extend(color.w);
+    }
 }
@@ -0,0 +1,245 @@
+//! Compares the CPU reference rendering of the waves shader with the images in `tests/golden/`.
+//!
+//! When a shader change is meant to change the picture, regenerate them with
+//! `UPDATE_GOLDEN=1 cargo test -p shaders` and commit them with the change, so the review shows
+//! the new picture.
+
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::Path,
+};
+
+use shaders::{
+    glam::{Vec2, Vec4},
+    reference::Image,
+    waves::{Globals, Layer, Properties, Ripple, RIPPLES},
+};
+
+const WIDTH: u32 = 160;
+const HEIGHT: u32 = 90;
+/// `sin` may round differently between platforms, so one step per channel is allowed.
+const TOLERANCE: u8 = 1;
+
+fn check(name: &str, properties: Properties, globals: Globals) {
+    let image = Image::render_waves(&properties, &globals, WIDTH, HEIGHT);
+    let golden_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let golden_path = golden_dir.join(format!("{}.png", name));
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(&golden_dir).unwrap();
+        save_png(&image, &golden_path).unwrap();
+        return;
+    }
+
+    let golden = load_png(&golden_path).unwrap_or_else(|e| {
+        panic!(
+            "{}: {}, run with UPDATE_GOLDEN=1 to create it",
+            golden_path.display(),
+            e
+        )
+    });
+    let difference = image.max_difference(&golden);
+    if difference.map_or(true, |difference| difference > TOLERANCE) {
+        let actual_path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("{}.png", name));
+        save_png(&image, &actual_path).unwrap();
+        panic!(
+            "{} does not match {} (largest channel difference: {:?})",
+            actual_path.display(),
+            golden_path.display(),
+            difference
+        );
+    }
+}
+
+fn save_png(image: &Image, path: &Path) -> Result<(), png::EncodingError> {
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(File::create(path)?),
+        image.width,
+        image.height,
+    );
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&image.pixels)
+}
+
+/// Only reads the RGBA8 images written by `save_png`.
+fn load_png(path: &Path) -> Result<Image, png::DecodingError> {
+    let mut reader = png::Decoder::new(File::open(path)?).read_info()?;
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels)?;
+    if info.color_type != png::ColorType::Rgba || info.bit_depth != png::BitDepth::Eight {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is not an RGBA8 image", path.display()),
+        )
+        .into());
+    }
+    pixels.truncate(info.buffer_size());
+
+    Ok(Image {
+        width: info.width,
+        height: info.height,
+        pixels,
+    })
+}
+
+/// Globals of a `WIDTH`×`HEIGHT` viewport without ripples.
+fn globals(time: f32) -> Globals {
+    let viewport = Vec2::new(WIDTH as f32, HEIGHT as f32);
+    Globals::new(time, viewport, 1, [Ripple::default(); RIPPLES])
+}
+
+/// The front layer at the start of the menu and in-game color tween.
+fn sea_green() -> Layer {
+    Layer {
+        amplitude: 0.2,
+        angular_velocity: 0.8,
+        frequency: 3.0,
+        offset: 0.5,
+        color: Vec4::new(0.18, 0.55, 0.34, 1.0),
+        steepness: 0.5,
+        direction: 1.0,
+        foam_width: 0.02,
+        edge_softness: 0.003,
+        foam_color: Vec4::new(1.0, 1.0, 1.0, 0.8),
+        deep_color: Vec4::new(0.02, 0.15, 0.25, 1.0),
+    }
+}
+
+/// Only the front layer is drawn.
+fn single(front: Layer) -> Properties {
+    let hidden = Layer {
+        color: Vec4::ZERO,
+        ..front
+    };
+    Properties {
+        layers: [hidden, hidden, front],
+    }
+}
+
+/// Copies of the front layer, higher, slower and more transparent towards the back.
+fn layered(front: Layer) -> Properties {
+    let behind = |depth: f32| Layer {
+        frequency: front.frequency * 0.7_f32.powf(depth),
+        offset: front.offset - 0.04 * depth,
+        color: front.color * Vec4::new(1.0, 1.0, 1.0, 0.6_f32.powf(depth)),
+        ..front
+    };
+    Properties {
+        layers: [behind(2.0), behind(1.0), front],
+    }
+}
+
+#[test]
+fn calm() {
+    check("calm", single(sea_green()), globals(0.0));
+}
+
+#[test]
+fn moving() {
+    let front = Layer {
+        amplitude: 0.15,
+        frequency: 3.5,
+        offset: 0.3,
+        color: Vec4::new(1.0, 0.08, 0.58, 1.0),
+        ..sea_green()
+    };
+    check("moving", single(front), globals(1.5));
+}
+
+#[test]
+fn layers() {
+    check("layers", layered(sea_green()), globals(1.5));
+}
+
+/// The plain sine of the original shader: no steepness, foam, edge softening or gradient.
+#[test]
+fn plain() {
+    let front = Layer {
+        steepness: 0.0,
+        foam_width: 0.0,
+        edge_softness: 0.0,
+        deep_color: sea_green().color,
+        ..sea_green()
+    };
+    check("plain", single(front), globals(1.5));
+}
+
+#[test]
+fn steep_right() {
+    let front = Layer {
+        steepness: 1.0,
+        direction: -1.0,
+        foam_width: 0.05,
+        ..sea_green()
+    };
+    check("steep_right", single(front), globals(1.5));
+}
+
+/// One fresh ripple near the surface and one that has spread out, the rest of the ring is empty.
+#[test]
+fn ripples() {
+    let mut globals = globals(2.0);
+    globals.ripples[0] = Ripple {
+        position: Vec2::new(0.3, 0.5),
+        start: 1.8,
+        strength: 0.04,
+    };
+    globals.ripples[5] = Ripple {
+        position: Vec2::new(0.7, 0.6),
+        start: 0.5,
+        strength: 0.05,
+    };
+    check("ripples", single(sea_green()), globals);
+}
+
+#[test]
+fn transparent() {
+    let front = Layer {
+        color: Vec4::ZERO,
+        ..sea_green()
+    };
+    check("transparent", single(front), globals(0.0));
+}
+
+/// Twice the pixels show the same picture: every 2×2 block averages to the matching pixel
+/// of the normal rendering, except along the edges, which land on different pixels.
+#[test]
+fn resolution_independent() {
+    let properties = layered(sea_green());
+    let render = |scale: u32| {
+        let (width, height) = (WIDTH * scale, HEIGHT * scale);
+        let viewport = Vec2::new(width as f32, height as f32);
+        let globals = Globals::new(1.5, viewport, 1, [Ripple::default(); RIPPLES]);
+        Image::render_waves(&properties, &globals, width, height)
+    };
+    let small = render(1);
+    let large = render(2);
+
+    let pixel = |image: &Image, x: u32, y: u32, channel: u32| {
+        f32::from(image.pixels[((y * image.width + x) * 4 + channel) as usize])
+    };
+    let mut matching = 0;
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let close = (0..4).all(|channel| {
+                let average = (0..4)
+                    .map(|i| pixel(&large, 2 * x + i % 2, 2 * y + i / 2, channel))
+                    .sum::<f32>()
+                    / 4.0;
+                (average - pixel(&small, x, y, channel)).abs() <= 2.0
+            });
+            matching += close as u32;
+        }
+    }
+
+    let total = WIDTH * HEIGHT;
+    assert!(
+        matching * 100 >= total * 95,
+        "only {} of {} pixels match",
+        matching,
+        total
+    );
+}
@@ -0,0 +1,150 @@
+//! The vertex shader lifts the top edge of a subdivided waves mesh onto the water, these check
+//! that no water is cut off between two of its vertices.
+
+use shaders::{
+    glam::{Mat4, Vec2, Vec3, Vec4},
+    waves::{
+        ripple_height, strip_top, surface_height, waves_vert, Globals, Layer, Mesh2d, Properties,
+        Ripple, View, RIPPLES,
+    },
+};
+
+const COLUMNS: u32 = 32;
+
+fn layer(offset: f32, steepness: f32) -> Layer {
+    Layer {
+        amplitude: 0.05,
+        angular_velocity: 0.8,
+        frequency: 3.0,
+        offset,
+        color: Vec4::new(0.18, 0.55, 0.34, 1.0),
+        steepness,
+        direction: 1.0,
+        foam_width: 0.02,
+        edge_softness: 0.003,
+        foam_color: Vec4::ONE,
+        deep_color: Vec4::new(0.02, 0.15, 0.25, 1.0),
+    }
+}
+
+fn globals(time: f32, columns: u32) -> Globals {
+    let mut ripples = [Ripple::default(); RIPPLES];
+    ripples[0] = Ripple {
+        position: Vec2::new(0.4, 0.5),
+        start: time - 0.5,
+        strength: 0.02,
+    };
+    Globals::new(time, Vec2::new(1280.0, 720.0), columns, ripples)
+}
+
+/// Runs the vertex shader with identity transforms, returns the output uv.
+fn vertex(properties: &Properties, globals: &Globals, uv: Vec2) -> Vec2 {
+    let (mut clip, mut world, mut normal, mut out_uv) =
+        (Vec4::ZERO, Vec4::ZERO, Vec3::ZERO, Vec2::ZERO);
+    let position = Vec3::new(uv.x - 0.5, 0.5 - uv.y, 0.0);
+    waves_vert(
+        position,
+        Vec3::Z,
+        uv,
+        &View {
+            view_proj: Mat4::IDENTITY,
+        },
+        properties,
+        globals,
+        &Mesh2d {
+            model: Mat4::IDENTITY,
+            inverse_transpose_model: Mat4::IDENTITY,
+        },
+        &mut clip,
+        &mut world,
+        &mut normal,
+        &mut out_uv,
+    );
+    // The position moves with the uv, so the interpolated uv still matches the pixel.
+    assert!((clip.y - (0.5 - out_uv.y)).abs() < 1e-5);
+    out_uv
+}
+
+#[test]
+fn top_edge_stays_above_the_water() {
+    for steepness in [0.0, 0.5, 0.9] {
+        let properties = Properties {
+            layers: [
+                layer(0.3, steepness),
+                layer(0.4, steepness),
+                layer(0.5, steepness),
+            ],
+        };
+        for time in [0.0, 1.5, 7.25] {
+            let globals = globals(time, COLUMNS);
+            let tops: Vec<f32> = (0..=COLUMNS)
+                .map(|column| {
+                    vertex(
+                        &properties,
+                        &globals,
+                        Vec2::new(column as f32 / COLUMNS as f32, 0.0),
+                    )
+                    .y
+                })
+                .collect();
+
+            for (column, edge) in tops.windows(2).enumerate() {
+                for step in 0..=16 {
+                    let t = step as f32 / 16.0;
+                    let u = (column as f32 + t) / COLUMNS as f32;
+                    let top = edge[0] + (edge[1] - edge[0]) * t;
+                    for layer in &properties.layers {
+                        let x = u * globals.aspect;
+                        let water = layer.offset
+                            + surface_height(layer, x, time)
+                            + ripple_height(&globals, x, layer.offset)
+                            - layer.edge_softness;
+                        assert!(
+                            top <= water,
+                            "steepness {}, time {}: edge {} below the water {} at u = {}",
+                            steepness,
+                            time,
+                            top,
+                            water,
+                            u
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A single column is the old flat quad, its vertices stay where they are.
+#[test]
+fn quad_is_left_alone() {
+    let properties = Properties {
+        layers: [layer(0.3, 0.5), layer(0.4, 0.5), layer(0.5, 0.5)],
+    };
+    let globals = globals(1.5, 1);
+    for uv in [Vec2::ZERO, Vec2::X, Vec2::Y, Vec2::ONE] {
+        assert_eq!(vertex(&properties, &globals, uv), uv);
+    }
+}
+
+#[test]
+fn hidden_layers_are_ignored() {
+    let hidden = Layer {
+        color: Vec4::ZERO,
+        ..layer(0.0, 0.5)
+    };
+    let properties = Properties {
+        layers: [hidden, hidden, layer(0.5, 0.5)],
+    };
+    let top = strip_top(&properties, &globals(1.5, COLUMNS), 0.5);
+    assert!(
+        top > 0.3,
+        "the top edge follows the hidden layer up to {}",
+        top
+    );
+
+    let properties = Properties {
+        layers: [hidden; 3],
+    };
+    assert_eq!(strip_top(&properties, &globals(1.5, COLUMNS), 0.5), 1.0);
+}
@@ -0,0 +1,228 @@
+//! 检查 `inject` 用到的资源：文件存在并且能被解析，同时列出没有被用到的文件。
+//!
+//! 资源路径直接从 `inject/src` 的字符串字面量里收集（包括生成的 `shader_paths.rs`），
+//! 以已知的资源后缀结尾的字面量都算一次引用。
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ffi::OsStr,
+    fs,
+    path::Path,
+};
+
+use anyhow::{bail, Context, Result};
+use xshell::cmd;
+
+use crate::{collect_files, project_root, translate};
+
+const SOURCES: &str = "inject/src";
+const ASSETS: &str = "inject/assets";
+
+/// 按后缀区分的资源种类，对应 `inject` 里用到的加载器。
+#[derive(Clone, Copy)]
+enum Kind {
+    Font,
+    Image,
+    SpirV,
+    Wgsl,
+    Difficulty,
+}
+
+impl Kind {
+    const SUFFIXES: [(&'static str, Self); 6] = [
+        (".ttf", Self::Font),
+        (".otf", Self::Font),
+        (".png", Self::Image),
+        (".spv", Self::SpirV),
+        (".wgsl", Self::Wgsl),
+        (".difficulty.ron", Self::Difficulty),
+    ];
+
+    /// 文件名在后缀前面必须还有内容，`extensions()` 里登记的 `"difficulty.ron"` 不算资源。
+    fn of(path: &str) -> Option<Self> {
+        if path.contains(char::is_whitespace) {
+            return None;
+        }
+        let name = path.rsplit('/').next()?;
+        Self::SUFFIXES
+            .iter()
+            .find(|(suffix, _)| name.len() > suffix.len() && name.ends_with(suffix))
+            .map(|&(_, kind)| kind)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Font => "font",
+            Self::Image => "PNG image",
+            Self::SpirV => "SPIR-V module",
+            Self::Wgsl => "WGSL module",
+            Self::Difficulty => "difficulty preset",
+        }
+    }
+
+    fn validate(self, bytes: &[u8]) -> Result<()> {
+        match self {
+            Self::Font => {
+                ab_glyph::FontRef::try_from_slice(bytes)?;
+            }
+            Self::Image => {
+                let mut reader = png::Decoder::new(bytes).read_info()?;
+                let mut buffer = vec![0; reader.output_buffer_size()];
+                reader.next_frame(&mut buffer)?;
+            }
+            Self::SpirV => {
+                translate::parse_spv(bytes)?;
+            }
+            Self::Wgsl => {
+                translate::parse_wgsl(std::str::from_utf8(bytes)?)?;
+            }
+            Self::Difficulty => {
+                check_difficulty(bytes)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn check_assets() -> Result<()> {
+    let root = project_root();
+    // 难度预设要按 `inject` 里真正的 `Difficulty` 解析和检查，先编译好检查用的程序
+    cmd!("cargo build -p inject --bin check_difficulty").run()?;
+    let assets = root.join(ASSETS);
+    let references = references(&root, &root.join(SOURCES), &assets)?;
+
+    let mut broken = Vec::new();
+    for (path, places) in &references {
+        let kind = Kind::of(path).unwrap();
+        let result = fs::read(assets.join(path))
+            .context("file not found")
+            .and_then(|bytes| {
+                kind.validate(&bytes)
+                    .with_context(|| format!("not a valid {}", kind.name()))
+            });
+        if let Err(e) = result {
+            broken.push(format!(
+                "{}: {:#}\n    used at {}",
+                path,
+                e,
+                places.join(", ")
+            ));
+        }
+    }
+
+    for file in asset_files(&assets)? {
+        // `cargo shader` 的哈希记录跟着对应的模块走
+        let owner = file.strip_suffix(".hash").unwrap_or(&file);
+        if !references.contains_key(owner) {
+            println!("unused: {}/{}", ASSETS, file);
+        }
+    }
+
+    if !broken.is_empty() {
+        bail!("{} broken asset(s):\n{}", broken.len(), broken.join("\n"));
+    }
+    println!("{} assets are fine", references.len());
+    Ok(())
+}
+
+/// 用 `inject` 的 `check_difficulty` 检查一个难度预设，和游戏加载时一样解析成 `Difficulty`
+/// 并验证，拼错的字段和不能玩的波次都会报错。
+fn check_difficulty(bytes: &[u8]) -> Result<()> {
+    let output = cmd!("cargo run -q -p inject --bin check_difficulty")
+        .echo_cmd(false)
+        .stdin(bytes)
+        .ignore_status()
+        .output()?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+/// 资源路径到引用位置（`文件:行号`）的映射。
+fn references(root: &Path, sources: &Path, assets: &Path) -> Result<BTreeMap<String, Vec<String>>> {
+    let mut files = Vec::new();
+    collect_files(sources, &mut files)?;
+    files.retain(|file| file.extension() == Some(OsStr::new("rs")));
+    files.sort();
+
+    let mut references = BTreeMap::<_, Vec<_>>::new();
+    for file in files {
+        let source =
+            fs::read_to_string(&file).with_context(|| format!("reading {}", file.display()))?;
+        let location = file
+            .strip_prefix(root)?
+            .to_string_lossy()
+            .replace('\\', "/");
+        for (line, literal) in string_literals(&source) {
+            let path = match asset_path(&file, &literal, assets) {
+                Some(path) if Kind::of(&path).is_some() => path,
+                _ => continue,
+            };
+            references
+                .entry(path)
+                .or_default()
+                .push(format!("{}:{}", location, line));
+        }
+    }
+    Ok(references)
+}
+
+/// 资源路径相对于 `assets`，只有 `include_bytes!("../assets/...")` 这样以 `../` 开头的路径
+/// 相对于源文件，换算过去；指向 `assets` 之外的不算资源。
+fn asset_path(source: &Path, literal: &str, assets: &Path) -> Option<String> {
+    if !literal.starts_with("../") {
+        return Some(literal.to_string());
+    }
+    let mut path = source.parent()?.to_path_buf();
+    for part in literal.split('/') {
+        match part {
+            ".." => {
+                path.pop();
+            }
+            "." => {}
+            _ => path.push(part),
+        }
+    }
+    let relative = path.strip_prefix(assets).ok()?;
+    Some(relative.to_string_lossy().replace('\\', "/"))
+}
+
+/// 每一行里完整的字符串字面量和它的行号，跳过行注释。
+///
+/// 只处理 `inject` 里实际出现的写法：不认识原始字符串和跨行的字符串，资源路径也用不到它们。
+fn string_literals(source: &str) -> Vec<(usize, String)> {
+    let mut literals = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        let mut chars = line.chars();
+        let mut literal: Option<String> = None;
+        while let Some(c) = chars.next() {
+            match (&mut literal, c) {
+                (None, '/') if chars.as_str().starts_with('/') => break,
+                (None, '"') => literal = Some(String::new()),
+                (None, _) => {}
+                (Some(_), '"') => literals.push((index + 1, literal.take().unwrap())),
+                (Some(text), '\\') => {
+                    text.push(c);
+                    text.extend(chars.next());
+                }
+                (Some(text), _) => text.push(c),
+            }
+        }
+    }
+    literals
+}
+
+/// `assets` 下所有文件的相对路径，跳过 `.DS_Store` 这类隐藏文件。
+fn asset_files(assets: &Path) -> Result<BTreeSet<String>> {
+    let mut files = Vec::new();
+    collect_files(assets, &mut files)?;
+    Ok(files
+        .iter()
+        .filter(|file| !file.file_name().unwrap().to_string_lossy().starts_with('.'))
+        .map(|file| {
+            let relative = file.strip_prefix(assets).unwrap();
+            relative.to_string_lossy().replace('\\', "/")
+        })
+        .collect())
+}
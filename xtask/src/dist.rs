@@ -0,0 +1,145 @@
+//! 在本地重复 `.github/workflows/release.yaml` 的打包步骤，打 tag 之前先检查发布包。
+
+use std::{
+    fs::{self, File},
+    io,
+    path::Path,
+};
+
+use anyhow::{bail, Context, Result};
+use xshell::cmd;
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+use crate::project_root;
+
+/// 与 release.yaml 里的 `env.binary` 一致
+const BINARY: &str = "inject";
+
+pub fn dist(target: &str, wasm_opt: bool) -> Result<()> {
+    let root = project_root();
+    let dist = root.join("target/dist");
+    let package = dist.join(target);
+    if package.exists() {
+        fs::remove_dir_all(&package)?;
+    }
+    fs::create_dir_all(&package)?;
+
+    match target {
+        "linux" => {
+            let triple = "x86_64-unknown-linux-gnu";
+            cmd!("cargo build --release --target {triple}")
+                .run()
+                .context("release build failed")?;
+            let binary = root
+                .join("target")
+                .join(triple)
+                .join("release")
+                .join(BINARY);
+            fs::copy(&binary, package.join(BINARY))
+                .with_context(|| format!("copying {}", binary.display()))?;
+        }
+        "wasm" => {
+            if wasm_opt {
+                check_tool(
+                    "wasm-opt",
+                    "binaryen (https://github.com/WebAssembly/binaryen)",
+                )?;
+            }
+            check_tool("wasm-bindgen", "`cargo install wasm-bindgen-cli`")?;
+
+            cmd!("cargo build --release --target wasm32-unknown-unknown")
+                .run()
+                .context("release build failed")?;
+            let wasm = root.join(format!(
+                "target/wasm32-unknown-unknown/release/{}.wasm",
+                BINARY
+            ));
+            cmd!("wasm-bindgen --no-typescript --out-name bevy_game --out-dir {package} --target web {wasm}")
+                .run()
+                .context("wasm-bindgen failed")?;
+            if wasm_opt {
+                let output = package.join("bevy_game_bg.wasm");
+                cmd!("wasm-opt -Oz -o {output} {output}")
+                    .run()
+                    .context("wasm-opt failed")?;
+            }
+            fs::copy(root.join("wasm/index.html"), package.join("index.html"))?;
+        }
+        _ => bail!("unknown target `{}`, expected `linux` or `wasm`", target),
+    }
+    copy_dir(&root.join("inject/assets"), &package.join("assets"))?;
+
+    let version = cmd!("git describe --tags --always --dirty")
+        .read()
+        .context("reading the version from git")?;
+    let archive = dist.join(format!("{}-{}-{}.zip", BINARY, target, version));
+    zip_dir(&package, &archive)?;
+    println!("packaged {}", archive.display());
+    Ok(())
+}
+
+fn check_tool(tool: &str, install: &str) -> Result<()> {
+    if cmd!("{tool} --version").echo_cmd(false).read().is_err() {
+        bail!("`{}` not found, install it with {}", tool, install);
+    }
+    Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let path = entry?.path();
+        let target = to.join(path.file_name().unwrap());
+        if path.is_dir() {
+            copy_dir(&path, &target)?;
+        } else {
+            fs::copy(&path, &target).with_context(|| format!("copying {}", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// 和 CI 一样，压缩包里的文件都放在以目标平台命名的文件夹下。
+fn zip_dir(dir: &Path, archive: &Path) -> Result<()> {
+    let mut zip = ZipWriter::new(File::create(archive)?);
+    add_to_zip(&mut zip, dir, dir.parent().unwrap())?;
+    zip.finish()?;
+    Ok(())
+}
+
+fn add_to_zip(zip: &mut ZipWriter<File>, dir: &Path, base: &Path) -> Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    for path in entries {
+        let name = path
+            .strip_prefix(base)?
+            .to_string_lossy()
+            .replace('\\', "/");
+        let options = FileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .unix_permissions(permissions(&path)?);
+        if path.is_dir() {
+            zip.add_directory(name, options)?;
+            add_to_zip(zip, &path, base)?;
+        } else {
+            zip.start_file(name, options)?;
+            io::copy(&mut File::open(&path)?, zip)?;
+        }
+    }
+    Ok(())
+}
+
+/// 保留可执行权限，解压后可以直接运行 linux 版本
+#[cfg(unix)]
+fn permissions(path: &Path) -> Result<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Ok(fs::metadata(path)?.permissions().mode() & 0o777)
+}
+
+#[cfg(not(unix))]
+fn permissions(path: &Path) -> Result<u32> {
+    Ok(if path.is_dir() { 0o755 } else { 0o644 })
+}
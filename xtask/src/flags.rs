@@ -10,7 +10,26 @@ xflags::xflags! {
         }
         /// 编译着色器
         cmd shader {
+            /// 同时把生成的 SPIR-V 翻译成 WGSL（网页版使用）
+            optional -a,--translate
+            /// 额外生成 GLSL ES 3.0（隐含 -a）
+            optional --glsl
+            /// 监视 shaders 的源码，保存后自动重新编译，失败时保留上一次的结果
+            optional -w,--watch
+            /// 不编译，只检查提交的 SPIR-V 是否和当前源码一致，过期时失败
+            optional --check
+            /// 源码没变也重新编译
+            optional -f,--force
         }
+        /// 按 release.yaml 的步骤打包，结果放在 target/dist
+        cmd dist {
+            /// 目标平台：linux 或 wasm
+            required --target target: String
+            /// 用 wasm-opt 压缩 wasm 版本
+            optional --wasm-opt
+        }
+        /// 检查 inject 引用的资源是否存在、能否解析，并列出没用到的文件
+        cmd check-assets {}
     }
 }
 // generated start
@@ -25,6 +44,8 @@ pub struct Xtask {
 pub enum XtaskCmd {
     Help(Help),
     Shader(Shader),
+    Dist(Dist),
+    CheckAssets(CheckAssets),
 }
 
 #[derive(Debug)]
@@ -33,7 +54,22 @@ pub struct Help {
 }
 
 #[derive(Debug)]
-pub struct Shader;
+pub struct Shader {
+    pub translate: bool,
+    pub glsl: bool,
+    pub watch: bool,
+    pub check: bool,
+    pub force: bool,
+}
+
+#[derive(Debug)]
+pub struct Dist {
+    pub target: String,
+    pub wasm_opt: bool,
+}
+
+#[derive(Debug)]
+pub struct CheckAssets;
 
 impl Xtask {
     pub const HELP: &'static str = Self::HELP_;
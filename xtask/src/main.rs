@@ -1,11 +1,16 @@
+mod assets;
+mod dist;
 mod flags;
+mod stamp;
+mod translate;
+mod watch;
 
 use std::{
-    env,
+    env, fs,
     path::{Path, PathBuf},
 };
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use xshell::{cmd, pushd};
 
 fn main() -> Result<()> {
@@ -18,13 +23,76 @@ fn main() -> Result<()> {
             println!("{}", flags::Xtask::HELP);
             Ok(())
         }
-        flags::XtaskCmd::Shader(_) => {
-            cmd!("cargo +nightly-2022-01-13 run -r -p shaders-builder")
-                .run()
-                .expect("Building sharders failed.");
-            Ok(())
+        flags::XtaskCmd::Shader(shader) => {
+            let root = project_root();
+            if shader.check {
+                let stale = stamp::stale_modules(&root, &assets_dir())?;
+                if !stale.is_empty() {
+                    bail!(
+                        "shaders are out of date, run `cargo shader`:\n{}",
+                        stale.join("\n")
+                    );
+                }
+                println!("shaders are up to date");
+                return Ok(());
+            }
+
+            if shader.watch {
+                let staging = root.join("target/shaders-watch");
+                let sources = [root.join("shaders/src"), root.join("shaders/Cargo.toml")];
+                return watch::watch(&sources, || {
+                    if staging.exists() {
+                        fs::remove_dir_all(&staging)?;
+                    }
+                    build_shaders(&["--out-dir".to_string(), staging.display().to_string()])?;
+                    watch::install(&staging, &assets_dir())?;
+                    stamp::write_stamps(&root, &assets_dir())?;
+                    translate_shaders(&shader)
+                });
+            }
+
+            if !shader.force && stamp::stale_modules(&root, &assets_dir())?.is_empty() {
+                println!("shaders are up to date, skipping the build (use --force to rebuild)");
+            } else {
+                build_shaders(&[])?;
+                stamp::write_stamps(&root, &assets_dir())?;
+            }
+            translate_shaders(&shader)
+        }
+        flags::XtaskCmd::Dist(dist) => dist::dist(&dist.target, dist.wasm_opt),
+        flags::XtaskCmd::CheckAssets(_) => assets::check_assets(),
+    }
+}
+
+/// 用和 `shaders-builder/rust-toolchain.toml` 一致的 nightly 运行 `shaders-builder`。
+fn build_shaders(args: &[String]) -> Result<()> {
+    cmd!("cargo +nightly-2022-01-13 run -r -p shaders-builder -- {args...}")
+        .run()
+        .context("building shaders failed")?;
+    Ok(())
+}
+
+fn translate_shaders(shader: &flags::Shader) -> Result<()> {
+    if shader.translate || shader.glsl {
+        translate::translate_dir(&assets_dir(), shader.glsl)?;
+    }
+    Ok(())
+}
+
+fn assets_dir() -> PathBuf {
+    project_root().join("inject/assets/shaders")
+}
+
+/// `path` 本身或者它下面的所有文件，递归进入子目录。
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    if path.is_dir() {
+        for entry in fs::read_dir(path).with_context(|| format!("reading {}", path.display()))? {
+            collect_files(&entry?.path(), files)?;
         }
+    } else {
+        files.push(path.to_path_buf());
     }
+    Ok(())
 }
 
 fn project_root() -> PathBuf {
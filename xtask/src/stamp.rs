@@ -0,0 +1,99 @@
+//! 记录每个 SPIR-V 模块是由哪一版源码编译出来的。
+//!
+//! 每个 `{name}.spv` 旁边都有一个 `{name}.spv.hash`，源码没变时 `cargo shader` 跳过编译，
+//! `cargo shader --check` 用它发现提交的模块已经过期：
+//!
+//! ```text
+//! sources <着色器源码、builder 和工具链配置的 SHA-256>
+//! module <这个 .spv 的 SHA-256>
+//! ```
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+
+use crate::{collect_files, translate};
+
+/// 影响编译结果的文件和目录，相对于项目根目录。工具链由 `rust-toolchain.toml` 固定。
+const INPUTS: &[&str] = &[
+    "shaders/Cargo.toml",
+    "shaders/src",
+    "shaders-builder/Cargo.toml",
+    "shaders-builder/rust-toolchain.toml",
+    "shaders-builder/shaders.ron",
+    "shaders-builder/src",
+];
+
+/// 所有输入文件的哈希，按相对路径排序，换行统一成 `\n`，在 Windows 上检出也一样。
+pub fn sources_hash(root: &Path) -> Result<String> {
+    let mut files = Vec::new();
+    for input in INPUTS {
+        collect_files(&root.join(input), &mut files)?;
+    }
+    files.sort();
+
+    let mut hasher = Sha256::new();
+    for file in files {
+        let relative = file
+            .strip_prefix(root)?
+            .to_string_lossy()
+            .replace('\\', "/");
+        let contents = fs::read(&file).with_context(|| format!("reading {}", file.display()))?;
+        let contents = String::from_utf8_lossy(&contents).replace("\r\n", "\n");
+        hasher.update(relative.as_bytes());
+        hasher.update([0]);
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(contents.as_bytes());
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn stamp_path(spv: &Path) -> PathBuf {
+    spv.with_extension("spv.hash")
+}
+
+fn stamp(sources: &str, spv: &Path) -> Result<String> {
+    let module = Sha256::digest(fs::read(spv)?);
+    Ok(format!("sources {}\nmodule {:x}\n", sources, module))
+}
+
+/// 给 `assets` 里的每个模块写上当前源码的哈希，编译成功后调用。
+pub fn write_stamps(root: &Path, assets: &Path) -> Result<()> {
+    let sources = sources_hash(root)?;
+    for spv in translate::spv_files(assets)? {
+        let path = stamp_path(&spv);
+        fs::write(&path, stamp(&sources, &spv)?)
+            .with_context(|| format!("writing {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// 和当前源码对不上的模块，每个一行说明原因；没有模块也算过期。
+pub fn stale_modules(root: &Path, assets: &Path) -> Result<Vec<String>> {
+    let sources = sources_hash(root)?;
+    let spvs = match translate::spv_files(assets) {
+        Ok(spvs) => spvs,
+        Err(e) => return Ok(vec![format!("{:#}", e)]),
+    };
+
+    let mut stale = Vec::new();
+    for spv in spvs {
+        let name = spv.file_name().unwrap().to_string_lossy();
+        match fs::read_to_string(stamp_path(&spv)) {
+            Err(_) => stale.push(format!(
+                "{}: has no hash, it was not built by `cargo shader`",
+                name
+            )),
+            Ok(recorded) if recorded.replace("\r\n", "\n") == stamp(&sources, &spv)? => {}
+            Ok(recorded) if !recorded.starts_with(&format!("sources {}\n", sources)) => {
+                stale.push(format!("{}: was built from older shader sources", name))
+            }
+            Ok(_) => stale.push(format!("{}: was changed after it was built", name)),
+        }
+    }
+    Ok(stale)
+}
@@ -0,0 +1,117 @@
+//! 把 `shaders-builder` 生成的 SPIR-V 翻译成 WebGL2 能用的格式。
+
+use std::{
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use naga::{
+    back::{glsl, wgsl},
+    front::spv,
+    valid::{Capabilities, ModuleInfo, ValidationFlags, Validator},
+    Module, ShaderStage,
+};
+
+/// 翻译 `dir` 下所有的 `.spv`，结果写在旁边：`{name}.wgsl`，开启 `glsl` 时每个入口点再写一个
+/// `{name}.frag`/`{name}.vert`，按着色器阶段命名，Bevy 的资源加载器才能认出来。
+pub fn translate_dir(dir: &Path, glsl: bool) -> Result<()> {
+    for spv in spv_files(dir)? {
+        translate(&spv, glsl).with_context(|| format!("translating {}", spv.display()))?;
+    }
+    Ok(())
+}
+
+/// `dir` 下排好序的所有 `.spv` 文件，一个都没有时报错。
+pub fn spv_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut spvs = fs::read_dir(dir)
+        .with_context(|| format!("reading {}", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<PathBuf>, _>>()?;
+    spvs.retain(|path| path.extension() == Some(OsStr::new("spv")));
+    spvs.sort();
+
+    if spvs.is_empty() {
+        bail!("no .spv files in {}", dir.display());
+    }
+    Ok(spvs)
+}
+
+/// 像 wgpu 一样解析并验证 SPIR-V 模块。
+pub fn parse_spv(bytes: &[u8]) -> Result<(Module, ModuleInfo)> {
+    // rust-gpu 的顶点着色器已经按 wgpu 的约定输出裁剪坐标（y 朝上），不要再翻转一次。
+    let options = spv::Options {
+        adjust_coordinate_space: false,
+        ..Default::default()
+    };
+    let module = spv::parse_u8_slice(bytes, &options).context("parsing SPIR-V failed")?;
+    let info = validate(&module).context("the parsed module is invalid")?;
+    Ok((module, info))
+}
+
+/// 解析并验证 WGSL 源码，解析失败时带上 naga 标注过位置的错误信息。
+pub fn parse_wgsl(source: &str) -> Result<(Module, ModuleInfo)> {
+    let module = naga::front::wgsl::parse_str(source)
+        .map_err(|e| anyhow!("parsing WGSL failed:\n{}", e.emit_to_string(source)))?;
+    let info = validate(&module).context("the parsed module is invalid")?;
+    Ok((module, info))
+}
+
+fn translate(spv: &Path, glsl: bool) -> Result<()> {
+    let (module, info) = parse_spv(&fs::read(spv)?)?;
+
+    let wgsl = wgsl::write_string(&module, &info, wgsl::WriterFlags::empty())
+        .context("writing WGSL failed")?;
+    // 输出的 WGSL 要能被重新解析并通过验证，否则浏览器里同样会失败。
+    parse_wgsl(&wgsl).context("generated WGSL is broken")?;
+    write(&spv.with_extension("wgsl"), &wgsl)?;
+
+    if glsl {
+        for entry_point in &module.entry_points {
+            let extension = match entry_point.stage {
+                ShaderStage::Vertex => "vert",
+                ShaderStage::Fragment => "frag",
+                ShaderStage::Compute => bail!(
+                    "compute entry point {:?} has no GLSL ES 3.0 equivalent",
+                    entry_point.name
+                ),
+            };
+            let source = write_glsl(&module, &info, &entry_point.name, entry_point.stage)
+                .with_context(|| format!("writing GLSL for {:?} failed", entry_point.name))?;
+            write(&spv.with_extension(extension), &source)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn validate(module: &Module) -> Result<ModuleInfo> {
+    Ok(Validator::new(ValidationFlags::all(), Capabilities::empty()).validate(module)?)
+}
+
+fn write_glsl(
+    module: &Module,
+    info: &ModuleInfo,
+    entry_point: &str,
+    shader_stage: ShaderStage,
+) -> Result<String> {
+    let options = glsl::Options {
+        version: glsl::Version::Embedded(300),
+        ..Default::default()
+    };
+    let pipeline_options = glsl::PipelineOptions {
+        shader_stage,
+        entry_point: entry_point.to_string(),
+    };
+
+    let mut source = String::new();
+    glsl::Writer::new(&mut source, module, info, &options, &pipeline_options)?.write()?;
+    Ok(source)
+}
+
+fn write(path: &Path, contents: &str) -> Result<()> {
+    fs::write(path, contents).with_context(|| format!("writing {}", path.display()))?;
+    println!("wrote {}", path.display());
+    Ok(())
+}
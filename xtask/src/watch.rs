@@ -0,0 +1,82 @@
+//! `cargo xtask shader --watch`：保存着色器源码后自动重新编译。
+//!
+//! 先编译到暂存目录，所有模块都通过 naga 验证后才替换 `inject/assets/shaders` 里的文件。
+//! 编译或验证失败时只打印错误，开着 `dev` 特性的游戏继续用上一次成功的着色器。
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context, Result};
+
+use crate::translate;
+
+/// 检查源码修改时间的间隔。
+const POLL: Duration = Duration::from_millis(500);
+
+/// 先执行一次 `rebuild`，之后 `sources` 里的文件一变就再执行，直到进程被结束。
+pub fn watch(sources: &[PathBuf], mut rebuild: impl FnMut() -> Result<()>) -> Result<()> {
+    let mut last = snapshot(sources)?;
+    report(rebuild());
+
+    loop {
+        thread::sleep(POLL);
+        let current = snapshot(sources)?;
+        if current != last {
+            last = current;
+            report(rebuild());
+        }
+    }
+}
+
+fn report(result: Result<()>) {
+    match result {
+        Ok(()) => println!("shaders updated, watching for changes"),
+        Err(e) => eprintln!(
+            "{:?}\nkeeping the last good shaders, watching for changes",
+            e
+        ),
+    }
+}
+
+/// 每个文件的修改时间，目录会递归展开。
+fn snapshot(sources: &[PathBuf]) -> Result<BTreeMap<PathBuf, SystemTime>> {
+    let mut times = BTreeMap::new();
+    let mut pending = sources.to_vec();
+    while let Some(path) = pending.pop() {
+        let metadata =
+            fs::metadata(&path).with_context(|| format!("reading {}", path.display()))?;
+        if metadata.is_dir() {
+            for entry in fs::read_dir(&path)? {
+                pending.push(entry?.path());
+            }
+        } else {
+            times.insert(path, metadata.modified()?);
+        }
+    }
+    Ok(times)
+}
+
+/// 把 `staging` 里的模块换进 `assets`。先全部验证再替换，免得只换掉一半。
+pub fn install(staging: &Path, assets: &Path) -> Result<()> {
+    let spvs = translate::spv_files(staging)?;
+    for spv in &spvs {
+        translate::parse_spv(&fs::read(spv)?)
+            .with_context(|| format!("validating {}", spv.display()))?;
+    }
+
+    for spv in &spvs {
+        let target = assets.join(spv.file_name().unwrap());
+        let temporary = target.with_extension("spv.tmp");
+        fs::copy(spv, &temporary)?;
+        // 重命名是原子的，游戏不会读到写了一半的文件
+        fs::rename(&temporary, &target)
+            .with_context(|| format!("replacing {}", target.display()))?;
+        println!("updated {}", target.display());
+    }
+    Ok(())
+}